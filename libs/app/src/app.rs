@@ -1,5 +1,5 @@
 use gfx::{Commands};
-use platform_types::{command, sprite, unscaled, Button, Input, Speaker, SFX};
+use platform_types::{command, sprite, unscaled, Button, Input, Speaker, Storage, SFX};
 pub use platform_types::StateParams;
 
 pub struct State {
@@ -7,10 +7,13 @@ pub struct State {
     pub commands: Commands,
     pub input: Input,
     pub speaker: Speaker,
+    /// The sounds from the updates since the last frame, handed to the platform.
+    pub playing: Speaker,
+    pub storage: Box<dyn Storage>,
 }
 
 impl State {
-    pub fn new((seed, logger, error_logger): StateParams) -> Self {
+    pub fn new((seed, logger, error_logger, today, storage): StateParams) -> Self {
         unsafe {
             features::GLOBAL_LOGGER = logger;
             features::GLOBAL_ERROR_LOGGER = error_logger;
@@ -20,31 +23,55 @@ impl State {
         // not the macro.
         features::log(&format!("{:?}", seed));
 
-        let mut game_state = game::State::new(seed);
+        let save_data = storage.load();
+
+        let mut game_state = game::State::new(seed, today, save_data.as_deref());
 
         Self {
             game_state,
             commands: Commands::default(),
             input: Input::default(),
             speaker: Speaker::default(),
+            playing: Speaker::default(),
+            storage,
         }
     }
 }
 
 impl platform_types::State for State {
-    fn frame(&mut self) -> (&[platform_types::Command], &[SFX]) {
+    fn update(&mut self, dt: f32) {
+        // Only the last update's commands are drawn, but every update's sounds
+        // are played.
         self.commands.clear();
-        self.speaker.clear();
         update_and_render(
             &mut self.commands,
             &mut self.game_state,
             self.input,
             &mut self.speaker,
+            dt,
         );
 
         self.input.previous_gamepad = self.input.gamepad;
+        self.input.previous_pointer_down = self.input.pointer_down;
+        self.input.clicks = 0;
+        self.input.scroll = 0;
+
+        if let Some(save_data) = self.game_state.take_save_data() {
+            if let Err(e) = self.storage.save(&save_data) {
+                features::log_error(&format!("Could not save: {}", e));
+            }
+        }
 
-        (self.commands.slice(), self.speaker.slice())
+        if !self.game_state.sound_enabled() {
+            self.speaker.clear();
+        }
+    }
+
+    fn frame(&mut self) -> (&[platform_types::Command], &[SFX]) {
+        core::mem::swap(&mut self.playing, &mut self.speaker);
+        self.speaker.clear();
+
+        (self.commands.slice(), self.playing.slice())
     }
 
     fn press(&mut self, button: Button) {
@@ -60,6 +87,23 @@ impl platform_types::State for State {
     fn release(&mut self, button: Button) {
         self.input.gamepad.remove(button);
     }
+
+    fn pointer_moved(&mut self, xy: Option<unscaled::XY>) {
+        self.input.pointer = xy;
+    }
+
+    fn pointer_pressed(&mut self) {
+        self.input.pointer_down = true;
+        self.input.clicks = self.input.clicks.saturating_add(1);
+    }
+
+    fn pointer_released(&mut self) {
+        self.input.pointer_down = false;
+    }
+
+    fn scrolled(&mut self, delta: i32) {
+        self.input.scroll = self.input.scroll.saturating_add(delta);
+    }
 }
 
 #[inline]
@@ -68,6 +112,7 @@ fn update_and_render(
     state: &mut game::State,
     input: Input,
     speaker: &mut Speaker,
+    dt: f32,
 ) {
-    game::State::update_and_render(commands, state, input, speaker);
+    game::State::update_and_render(commands, state, input, speaker, dt);
 }
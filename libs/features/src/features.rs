@@ -13,6 +13,10 @@ pub fn log(s: &str) {
     logger_log(unsafe { GLOBAL_LOGGER }, s)
 }
 
+pub fn log_error(s: &str) {
+    logger_log(unsafe { GLOBAL_ERROR_LOGGER }, s)
+}
+
 #[cfg(feature = "invariant-checking")]
 pub fn invariant_violation(s: &str) {
     logger_log(unsafe { GLOBAL_ERROR_LOGGER }, s)
@@ -1,7 +1,8 @@
 use common::*;
 use gfx::{Commands};
 use models::{Card, gen_card};
-use platform_types::{command, unscaled, Button, Input, Speaker, SFX};
+use platform_types::{command, unscaled, Button, Input, PaletteIndex, Speaker, ARGB, SFX};
+use state_manipulation::campaign;
 use xs::{Xs, Seed};
 
 pub struct State {
@@ -9,20 +10,36 @@ pub struct State {
     state: common::State,
     platform: Platform,
     events: Vec<Event>,
+    /// What was last loaded or saved, so we know when there is more to save.
+    saved: (Progress, Settings),
 }
 
-const TILE_SIZE: unscaled::Inner = 45;
+/// The size of each tile in the sprite sheet. Tiles on screen are scaled from
+/// this to fit the board into the space above the HUD.
+const SPRITE_TILE_SIZE: unscaled::Inner = 45;
 
 impl State {
-    pub fn new(seed: Seed) -> State {
+    pub fn new(seed: Seed, today: platform_types::Date, save_data: Option<&str>) -> State {
         let rng = xs::from_seed(seed);
 
+        let mut session = Session {
+            today: common::Date {
+                year: today.year,
+                month: today.month,
+                day: today.day,
+            },
+            ..Session::default()
+        };
+
+        if let Some(save_data) = save_data {
+            state_manipulation::save::load(save_data, &mut session);
+        }
+
+        let saved = (session.progress.clone(), session.settings);
+
         State {
             rng,
-            state: state_manipulation::new_state(Size::new(
-                16,
-                16
-            )),
+            state: state_manipulation::new_state(platform::size(), seed, session),
             platform: Platform {
                 print_xy: platform::print_xy,
                 clear: platform::clear,
@@ -39,36 +56,44 @@ impl State {
                 get_background: platform::get_background,
                 set_layer: platform::set_layer,
                 get_layer: platform::get_layer,
+                log: features::log,
             },
             events: Vec::with_capacity(1),
+            saved,
         }
     }
 
+    /// Returns the save data, if it has changed since it was loaded, or
+    /// last returned from here.
+    pub fn take_save_data(&mut self) -> Option<String> {
+        let session = &self.state.session;
+
+        if self.saved.0 == session.progress && self.saved.1 == session.settings {
+            return None
+        }
+
+        self.saved = (session.progress.clone(), session.settings);
+
+        Some(state_manipulation::save::to_string(session))
+    }
+
+    pub fn sound_enabled(&self) -> bool {
+        self.state.session.settings.sound
+    }
+
+    /// `dt` is how many seconds of game time to advance by.
     pub fn update_and_render(
         commands: &mut Commands,
         state: &mut State,
         input: Input,
         speaker: &mut Speaker,
+        dt: f32,
     ) {
         state.events.clear();
         for button in Button::ALL {
-            macro_rules! button_to_key {
-                ($button: ident) => {
-                    match $button {
-                        Button::A => KeyCode::R,
-                        Button::UP => KeyCode::Up,
-                        Button::DOWN => KeyCode::Down,
-                        Button::LEFT => KeyCode::Left,
-                        Button::RIGHT => KeyCode::Right,
-                        // Something the game doesn't respond to
-                        _ => KeyCode::MouseFifth
-                    }
-                }
-            }
-
             if input.pressed_this_frame(button) {
                 state.events.push(Event::KeyPressed {
-                    key: button_to_key!(button),
+                    key: button_to_key(button),
                     ctrl: false,
                     shift: false,
                 });
@@ -76,44 +101,275 @@ impl State {
 
             if input.released_this_frame(button) {
                 state.events.push(Event::KeyReleased {
-                    key: button_to_key!(button),
+                    key: button_to_key(button),
                     ctrl: false,
                     shift: false,
                 });
             }
         }
 
+        let mouse = match input.pointer {
+            Some(xy) => {
+                let cell = platform::cell_at(state.state.size, xy);
+
+                if cell != platform::mouse_position() {
+                    state.events.push(Event::MouseMove {
+                        x: cell.x,
+                        y: cell.y,
+                    });
+                }
+
+                cell
+            }
+            None => platform::mouse_position(),
+        };
+
+        for _ in 0..input.clicks {
+            state.events.push(Event::KeyPressed {
+                key: KeyCode::MouseLeft,
+                ctrl: false,
+                shift: false,
+            });
+        }
+
+        if input.pointer_released_this_frame() {
+            state.events.push(Event::KeyReleased {
+                key: KeyCode::MouseLeft,
+                ctrl: false,
+                shift: false,
+            });
+        }
+
+        if input.scroll != 0 {
+            state.events.push(Event::MouseScroll {
+                delta: input.scroll,
+            });
+        }
+
+        platform::set_input(input, mouse);
+
+        let before = Audible::of(&state.state);
+
         let _ignored = state_manipulation::update_and_render(
             &state.platform,
             &mut state.state,
-            &mut state.events
+            &mut state.events,
+            dt,
+        );
+
+        for sfx in sfx_between(before, Audible::of(&state.state)) {
+            speaker.request_sfx(sfx);
+        }
+
+        platform::push_commands(
+            commands,
+            state.state.size,
+            state_manipulation::player_draw_offset(&state.state),
         );
 
-        platform::push_commands(commands);
+        draw_hud(commands, &state.state);
 
         platform::end_frame();
     }
 }
 
+/// The parts of the state that sounds are played for changes in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Audible {
+    moving: bool,
+    /// Whether the title screen, the editor, (and whether it is
+    /// play-testing,) or the level select is showing.
+    screen: (bool, Option<bool>, bool),
+    sound: bool,
+}
+
+impl Audible {
+    fn of(state: &common::State) -> Self {
+        Audible {
+            moving: state.motion != Motion::Stopped,
+            screen: (
+                state.title_screen,
+                state.editor.map(|editor| editor.playtesting),
+                state.level_select.is_some(),
+            ),
+            sound: state.session.settings.sound,
+        }
+    }
+}
+
+/// The sounds to play for an update that went from `before` to `after`.
+fn sfx_between(before: Audible, after: Audible) -> Vec<SFX> {
+    let mut sfx = Vec::new();
+
+    if before.screen != after.screen {
+        sfx.push(SFX::ButtonPress);
+    } else {
+        match (before.moving, after.moving) {
+            (false, true) => sfx.push(SFX::CardSlide),
+            (true, false) => sfx.push(SFX::CardPlace),
+            _ => {}
+        }
+    }
+
+    // So there is something to hear that it worked.
+    if after.sound && !before.sound {
+        sfx.push(SFX::ButtonPress);
+    }
+
+    sfx
+}
+
+fn button_to_key(button: Button) -> KeyCode {
+    match button {
+        Button::A => KeyCode::R,
+        Button::B => KeyCode::X,
+        Button::SELECT => KeyCode::Tab,
+        Button::START => KeyCode::Enter,
+        Button::UP => KeyCode::Up,
+        Button::DOWN => KeyCode::Down,
+        Button::LEFT => KeyCode::Left,
+        Button::RIGHT => KeyCode::Right,
+        // Something the game doesn't respond to
+        _ => KeyCode::MouseFifth
+    }
+}
+
+const HUD_HEIGHT: unscaled::Inner = 16;
+const BOARD_AREA_W: unscaled::Inner = command::WIDTH;
+const BOARD_AREA_H: unscaled::Inner = command::HEIGHT - HUD_HEIGHT;
+
+const HUD_X: unscaled::X = unscaled::X(4);
+const HUD_Y: unscaled::Y = unscaled::Y(BOARD_AREA_H + 4);
+const HUD_COLOUR: PaletteIndex = 6; // white
+
+fn draw_hud(commands: &mut Commands, state: &common::State) {
+    if let Some(text) = hud_text(state) {
+        commands.print_line(text.as_bytes(), HUD_X, HUD_Y, HUD_COLOUR);
+    }
+}
+
+/// What goes in the strip under the board, if anything.
+fn hud_text(state: &common::State) -> Option<String> {
+    if state.title_screen {
+        return None
+    }
+
+    let moves = state_manipulation::move_count(state);
+
+    let text = match (state.editor, state.level_select) {
+        (Some(Editor { playtesting: true, .. }), _) => format!("EDITOR   MOVES {moves}"),
+        (Some(_), _) => "EDITOR".to_string(),
+        (None, Some(select)) => level_select_text(select.cursor, &state.session.progress),
+        (None, None) => {
+            let mut text = match (state.daily, state.campaign_level) {
+                (Some(daily), _) => format!("DAILY {}", daily.date),
+                (None, Some(id)) => level_title(id),
+                (None, None) => format!("LEVEL {}", state_manipulation::level_number(state)),
+            };
+
+            text.push_str(&format!("   MOVES {moves}"));
+
+            if let Some(par) = state.par {
+                text.push_str(&format!("   PAR {par}"));
+            }
+
+            let best = match (state.daily, state.campaign_level) {
+                (Some(daily), _) => daily.best,
+                (None, Some(id)) => state.session.progress.best.get(&id).cloned(),
+                (None, None) => None,
+            };
+
+            if let Some(best) = best {
+                text.push_str(&format!("   BEST {best}"));
+            }
+
+            if state.hints_used > 0 {
+                text.push_str(&format!("   HINTS {}", state.hints_used));
+            }
+
+            text
+        }
+    };
+
+    Some(text)
+}
+
+fn level_title(id: LevelId) -> String {
+    let name = campaign::level(id).map(|level| level.name).unwrap_or_default();
+
+    format!("{}-{} {name}", id.world + 1, id.level + 1)
+}
+
+fn level_select_text(cursor: LevelId, progress: &Progress) -> String {
+    let world = campaign::WORLDS.get(cursor.world).map(|world| world.name).unwrap_or_default();
+
+    let status = if progress.completed.contains(&cursor) {
+        match progress.best.get(&cursor) {
+            Some(best) => format!("BEST {best}"),
+            None => "COMPLETED".to_string(),
+        }
+    } else if campaign::is_unlocked(progress, cursor) {
+        "UNLOCKED".to_string()
+    } else {
+        "LOCKED".to_string()
+    };
+
+    format!("{}   {}   {status}", world, level_title(cursor))
+}
+
 mod platform {
     use super::*;
     use platform_types::{sprite};
     use std::{
-        collections::HashMap,
+        collections::BTreeMap,
         sync::{Mutex, OnceLock}
     };
 
     type X = unscaled::Inner;
     type Y = unscaled::Inner;
 
+    /// Something printed, along with the colours it was printed in.
+    #[derive(Clone, Copy)]
+    struct Tile {
+        s: &'static str,
+        foreground: Color,
+        background: Color,
+        /// Whether this was one character of a longer string. Those prefer
+        /// the font, so a word does not mix in glyphs from the tile atlas.
+        in_text: bool,
+    }
+
     struct State {
-        chars: HashMap<(X, Y), &'static str>,
+        /// What has been printed on each layer. Layers are drawn from the
+        /// lowest up, and each layer's cells in order, so the result does not
+        /// depend on the order things were printed in.
+        layers: BTreeMap<i32, BTreeMap<(X, Y), Tile>>,
+        foreground: Color,
+        background: Color,
+        layer: i32,
+        /// The buttons held down as of the current update.
+        gamepad: Button,
+        /// The cell the pointer was last over.
+        mouse: Point,
+        pointer_down: bool,
+        clicks: u8,
     }
-    
+
+    const DEFAULT_FOREGROUND: Color = Color { red: 0xEE, green: 0xEE, blue: 0xEE, alpha: 0xFF };
+    /// Fully transparent, so nothing is drawn behind the tiles.
+    const DEFAULT_BACKGROUND: Color = Color { red: 0, green: 0, blue: 0, alpha: 0 };
+
     fn state() -> &'static Mutex<State> {
         static STATE: OnceLock<Mutex<State>> = OnceLock::new();
         STATE.get_or_init(|| Mutex::new(State {
-            chars: HashMap::with_capacity(128),
+            layers: BTreeMap::new(),
+            foreground: DEFAULT_FOREGROUND,
+            background: DEFAULT_BACKGROUND,
+            layer: 0,
+            gamepad: Button::default(),
+            mouse: Point::default(),
+            pointer_down: false,
+            clicks: 0,
         }))
     }
 
@@ -124,120 +380,397 @@ mod platform {
     }
 
     /// `Platform` function pointers
-
+    /// Lays `s` out left to right, one character per cell, starting at
+    /// `(x_in, y_in)`.
     pub fn print_xy(x_in: i32, y_in: i32, s: &'static str) {
-        assert_eq!(s.chars().count(), 1, "{s}");
-
         match (X::try_from(x_in), Y::try_from(y_in)) {
             (Ok(x), Ok(y)) => {
-                state!().chars.insert((x, y), s);
+                let mut state = state!();
+                let in_text = s.chars().count() > 1;
+                let layer = state.layer;
+                let (foreground, background) = (state.foreground, state.background);
+                let cells = state.layers.entry(layer).or_default();
+
+                for (i, (byte_index, c)) in s.char_indices().enumerate() {
+                    let tile = Tile {
+                        s: &s[byte_index..byte_index + c.len_utf8()],
+                        foreground,
+                        background,
+                        in_text,
+                    };
+                    cells.insert((x + i as X, y), tile);
+                }
             },
             _ => {
                 assert!(false, "bad (x, y): ({x_in}, {y_in})");
             }
         }
     }
+    /// Clears every layer, or only the cells inside `rect` on the current
+    /// layer.
     pub fn clear(rect: Option<Rect>) {
+        let mut state = state!();
+        let layer = state.layer;
+
+        match rect {
+            None => state.layers.clear(),
+            Some(rect) => {
+                let Some(chars) = state.layers.get_mut(&layer) else {
+                    return
+                };
+
+                let Point { x: left, y: top } = rect.top_left;
+                let Size { width, height } = rect.size;
 
+                chars.retain(|&(x, y), _| {
+                    let (x, y) = (x as i32, y as i32);
+
+                    x < left || y < top || x >= left + width || y >= top + height
+                });
+            }
+        }
     }
+    /// The size of the title screen, editor and level select. Levels bring
+    /// their own size.
     pub fn size() -> Size {
         Size::new(16, 16)
     }
-    pub fn pick(point: Point, _: i32) -> char {
-        '\0'
+    /// Returns the character printed at `point` on the current layer, or
+    /// `'\0'` if there is none. Only one character is kept per cell on each
+    /// layer, so any `index` other than 0 also gives `'\0'`.
+    pub fn pick(point: Point, index: i32) -> char {
+        if index != 0 {
+            return '\0';
+        }
+
+        match (X::try_from(point.x), Y::try_from(point.y)) {
+            (Ok(x), Ok(y)) => {
+                let state = state!();
+
+                state.layers
+                    .get(&state.layer)
+                    .and_then(|chars| chars.get(&(x, y)))
+                    .and_then(|tile| tile.s.chars().next())
+                    .unwrap_or('\0')
+            },
+            _ => '\0',
+        }
     }
+    /// The cell the pointer was last over. This can be outside the board.
     pub fn mouse_position() -> Point {
-        Point::default()
+        state!().mouse
     }
+    /// How many times the pointer went down during the current update.
     pub fn clicks() -> i32 {
-        0
+        i32::from(state!().clicks)
     }
+    /// Whether any button that sends `key` is held down.
     pub fn key_pressed(key: KeyCode) -> bool {
-        false
+        let state = state!();
+
+        if key == KeyCode::MouseLeft {
+            return state.pointer_down;
+        }
+
+        Button::ALL
+            .iter()
+            .any(|&button| state.gamepad.contains(button) && button_to_key(button) == key)
     }
     pub fn set_colors(foreground: Color, background: Color) {
-        
+        let mut state = state!();
+        state.foreground = foreground;
+        state.background = background;
     }
     pub fn get_colors() -> (Color, Color) {
-        (
-            Color { red: 255, green: 0, blue: 255, alpha: 255 },
-            Color { red: 255, green: 0, blue: 255, alpha: 255 },
-        )
+        let state = state!();
+        (state.foreground, state.background)
     }
     pub fn set_foreground(foreground: Color) {
-
+        state!().foreground = foreground;
     }
-    pub fn get_foreground() -> (Color) {
-        get_colors().0
+    pub fn get_foreground() -> Color {
+        state!().foreground
     }
     pub fn set_background(background: Color) {
-
+        state!().background = background;
     }
-    pub fn get_background() -> (Color) {
-        get_colors().1
+    pub fn get_background() -> Color {
+        state!().background
     }
     pub fn set_layer(layer: i32) {
-
+        state!().layer = layer;
     }
     pub fn get_layer() -> i32 {
-        0
+        state!().layer
     }
 
     /// `platform` state management
-    pub fn push_commands(commands: &mut Commands) {
-        for ((x, y), s) in state!().chars.iter() {
-            let (sx, sy) = match *s {
-                "☐" => (0, 0),
-                "☒" => (1 * TILE_SIZE, 0),
-                "\u{E010}" => (2 * TILE_SIZE, 0),
-                "\u{E011}" => (3 * TILE_SIZE, 0),
-                "\u{E012}" => (4 * TILE_SIZE, 0),
-                "\u{E013}" => (5 * TILE_SIZE, 0),
-                "\u{E014}" => (6 * TILE_SIZE, 0),
-                "\u{E015}" => (7 * TILE_SIZE, 0),
-                "\u{E016}" => (8 * TILE_SIZE, 0),
-                "\u{E017}" => (9 * TILE_SIZE, 0),
-                "\u{E018}" => (10 * TILE_SIZE, 0),
-                "@" => (3 * TILE_SIZE, 1 * TILE_SIZE),
-                "R" => (4 * TILE_SIZE, 1 * TILE_SIZE),
-                "↑" => (3 * TILE_SIZE, 2 * TILE_SIZE),
-                "←" => (4 * TILE_SIZE, 2 * TILE_SIZE),
-                "↓" => (5 * TILE_SIZE, 2 * TILE_SIZE),
-                "→" => (6 * TILE_SIZE, 2 * TILE_SIZE),
-                "┌" => (3 * TILE_SIZE, 3 * TILE_SIZE),
-                "─" => (4 * TILE_SIZE, 3 * TILE_SIZE),
-                "╖" => (5 * TILE_SIZE, 3 * TILE_SIZE),
-                "│" => (6 * TILE_SIZE, 3 * TILE_SIZE),
-                "╘" => (7 * TILE_SIZE, 3 * TILE_SIZE),
-                "┘" => (8 * TILE_SIZE, 3 * TILE_SIZE),
-                "╔" => (3 * TILE_SIZE, 4 * TILE_SIZE),
-                "═" => (4 * TILE_SIZE, 4 * TILE_SIZE),
-                "╕" => (5 * TILE_SIZE, 4 * TILE_SIZE),
-                "║" => (6 * TILE_SIZE, 4 * TILE_SIZE),
-                "╙" => (7 * TILE_SIZE, 4 * TILE_SIZE),
-                "╝" => (8 * TILE_SIZE, 4 * TILE_SIZE),
-                _ => {
-                    debug_assert!(false, "unknown tile str: \"{s}\"");
-                    (0, 0)
-                }
+    /// `mouse` is the cell the pointer is over, as `cell_at` gives.
+    pub fn set_input(input: Input, mouse: Point) {
+        let mut state = state!();
+        state.gamepad = input.gamepad;
+        state.mouse = mouse;
+        state.pointer_down = input.pointer_down;
+        state.clicks = input.clicks;
+    }
+
+    /// How big each cell of a board of `board_size` is on screen, and where
+    /// its top left corner is, as `(tile_size, left, top)`.
+    pub fn board_layout(board_size: Size) -> (unscaled::Inner, unscaled::Inner, unscaled::Inner) {
+        let w = board_size.width.max(1) as unscaled::Inner;
+        let h = board_size.height.max(1) as unscaled::Inner;
+
+        let tile_size = core::cmp::max(
+            1,
+            core::cmp::min(BOARD_AREA_W / w, BOARD_AREA_H / h)
+        );
+
+        // Centre the board in the space for it.
+        let left = BOARD_AREA_W.saturating_sub(tile_size * w) / 2;
+        let top = BOARD_AREA_H.saturating_sub(tile_size * h) / 2;
+
+        (tile_size, left, top)
+    }
+
+    /// The cell of a board of `board_size` that `xy` is over. This is
+    /// outside the board if `xy` is.
+    pub fn cell_at(board_size: Size, xy: unscaled::XY) -> Point {
+        let (tile_size, left, top) = board_layout(board_size);
+        let tile_size = i32::from(tile_size);
+
+        Point {
+            x: (i32::from(xy.x.get()) - i32::from(left)).div_euclid(tile_size),
+            y: (i32::from(xy.y.get()) - i32::from(top)).div_euclid(tile_size),
+        }
+    }
+
+    /// `player_offset` is how far from their cell, in cells, to draw the player.
+    pub fn push_commands(commands: &mut Commands, board_size: Size, player_offset: (f32, f32)) {
+        let (tile_size, left, top) = board_layout(board_size);
+
+        for ((x, y), tile) in state!().layers.values().flat_map(|chars| chars.iter()) {
+            let s = tile.s;
+            let atlas_xy = if tile.in_text && s.is_ascii() {
+                None
+            } else {
+                atlas_xy(s)
+            };
+
+            let (dx, dy) = if s == "@" && !tile.in_text {
+                (
+                    (player_offset.0 * tile_size as f32) as i32,
+                    (player_offset.1 * tile_size as f32) as i32,
+                )
+            } else {
+                (0, 0)
             };
 
-            commands.sspr(
-                sprite::XY {
-                    x: sprite::X(sx),
-                    y: sprite::Y(sy),
-                },
-                command::Rect::from_unscaled(unscaled::Rect {
-                    x: unscaled::X((x * TILE_SIZE) as _),
-                    y: unscaled::Y((y * TILE_SIZE) as _),
-                    w: unscaled::W(TILE_SIZE),
-                    h: unscaled::H(TILE_SIZE),
-                })
-            );
+            let rect = command::Rect::from_unscaled(unscaled::Rect {
+                x: unscaled::X(offset(left + x * tile_size, dx)),
+                y: unscaled::Y(offset(top + y * tile_size, dy)),
+                w: unscaled::W(tile_size),
+                h: unscaled::H(tile_size),
+            });
+
+            if tile.background.alpha != 0 {
+                commands.sspr_scaled_override(
+                    sprite::XY {
+                        x: sprite::X(SOLID_SPRITE.0),
+                        y: sprite::Y(SOLID_SPRITE.1),
+                    },
+                    sprite::WH {
+                        w: unscaled::W(SPRITE_TILE_SIZE),
+                        h: unscaled::H(SPRITE_TILE_SIZE),
+                    },
+                    rect,
+                    to_argb(tile.background),
+                );
+            }
+
+            match atlas_xy {
+                Some((sx, sy)) => {
+                    // The sprites have shading that a colour override would
+                    // flatten, so they are left alone unless a colour was asked
+                    // for.
+                    let colour_override = if tile.foreground == DEFAULT_FOREGROUND {
+                        0
+                    } else {
+                        to_argb(tile.foreground)
+                    };
+
+                    commands.sspr_scaled_override(
+                        sprite::XY {
+                            x: sprite::X(sx),
+                            y: sprite::Y(sy),
+                        },
+                        sprite::WH {
+                            w: unscaled::W(SPRITE_TILE_SIZE),
+                            h: unscaled::H(SPRITE_TILE_SIZE),
+                        },
+                        rect,
+                        colour_override,
+                    );
+                }
+                None => {
+                    // The font only covers ASCII.
+                    let character = s.chars()
+                        .next()
+                        .filter(char::is_ascii)
+                        .map(|c| c as u8)
+                        .unwrap_or(b'?');
+
+                    commands.sspr_scaled_override(
+                        gfx::get_char_xy(character),
+                        sprite::WH {
+                            w: gfx::CHAR_W,
+                            h: gfx::CHAR_H,
+                        },
+                        rect,
+                        to_argb(tile.foreground),
+                    );
+                }
+            }
         }
     }
+
+    /// Where `s` is in the tile atlas, if it is there at all.
+    fn atlas_xy(s: &str) -> Option<(unscaled::Inner, unscaled::Inner)> {
+        Some(match s {
+            "☐" => (0, 0),
+            "☒" => (SPRITE_TILE_SIZE, 0),
+            "\u{E010}" => (2 * SPRITE_TILE_SIZE, 0),
+            "\u{E011}" => (3 * SPRITE_TILE_SIZE, 0),
+            "\u{E012}" => (4 * SPRITE_TILE_SIZE, 0),
+            "\u{E013}" => (5 * SPRITE_TILE_SIZE, 0),
+            "\u{E014}" => (6 * SPRITE_TILE_SIZE, 0),
+            "\u{E015}" => (7 * SPRITE_TILE_SIZE, 0),
+            "\u{E016}" => (8 * SPRITE_TILE_SIZE, 0),
+            "\u{E017}" => (9 * SPRITE_TILE_SIZE, 0),
+            "\u{E018}" => (10 * SPRITE_TILE_SIZE, 0),
+            // The goal, before every key is picked up.
+            "\u{E019}" => (9 * SPRITE_TILE_SIZE, SPRITE_TILE_SIZE),
+            "@" => (3 * SPRITE_TILE_SIZE, SPRITE_TILE_SIZE),
+            "R" => (4 * SPRITE_TILE_SIZE, SPRITE_TILE_SIZE),
+            "▒" => (5 * SPRITE_TILE_SIZE, SPRITE_TILE_SIZE),
+            "■" => (6 * SPRITE_TILE_SIZE, SPRITE_TILE_SIZE),
+            "░" => (7 * SPRITE_TILE_SIZE, SPRITE_TILE_SIZE),
+            "●" => (8 * SPRITE_TILE_SIZE, SPRITE_TILE_SIZE),
+            "⚷" => (10 * SPRITE_TILE_SIZE, SPRITE_TILE_SIZE),
+            "▦" => (7 * SPRITE_TILE_SIZE, 2 * SPRITE_TILE_SIZE),
+            "█" => SOLID_SPRITE,
+            "↑" => (3 * SPRITE_TILE_SIZE, 2 * SPRITE_TILE_SIZE),
+            "←" => (4 * SPRITE_TILE_SIZE, 2 * SPRITE_TILE_SIZE),
+            "↓" => (5 * SPRITE_TILE_SIZE, 2 * SPRITE_TILE_SIZE),
+            "→" => (6 * SPRITE_TILE_SIZE, 2 * SPRITE_TILE_SIZE),
+            "┌" => (3 * SPRITE_TILE_SIZE, 3 * SPRITE_TILE_SIZE),
+            "─" => (4 * SPRITE_TILE_SIZE, 3 * SPRITE_TILE_SIZE),
+            "╖" => (5 * SPRITE_TILE_SIZE, 3 * SPRITE_TILE_SIZE),
+            "│" => (6 * SPRITE_TILE_SIZE, 3 * SPRITE_TILE_SIZE),
+            "╘" => (7 * SPRITE_TILE_SIZE, 3 * SPRITE_TILE_SIZE),
+            "┘" => (8 * SPRITE_TILE_SIZE, 3 * SPRITE_TILE_SIZE),
+            "╔" => (3 * SPRITE_TILE_SIZE, 4 * SPRITE_TILE_SIZE),
+            "═" => (4 * SPRITE_TILE_SIZE, 4 * SPRITE_TILE_SIZE),
+            "╕" => (5 * SPRITE_TILE_SIZE, 4 * SPRITE_TILE_SIZE),
+            "║" => (6 * SPRITE_TILE_SIZE, 4 * SPRITE_TILE_SIZE),
+            "╙" => (7 * SPRITE_TILE_SIZE, 4 * SPRITE_TILE_SIZE),
+            "╝" => (8 * SPRITE_TILE_SIZE, 4 * SPRITE_TILE_SIZE),
+            _ => return None,
+        })
+    }
+
+    /// A tile in the sprite sheet with every pixel fully opaque, for filling
+    /// in backgrounds with.
+    const SOLID_SPRITE: (unscaled::Inner, unscaled::Inner) = (8 * SPRITE_TILE_SIZE, 2 * SPRITE_TILE_SIZE);
+
+    fn to_argb(colour: Color) -> ARGB {
+        u32::from(colour.alpha) << 24
+        | u32::from(colour.red) << 16
+        | u32::from(colour.green) << 8
+        | u32::from(colour.blue)
+    }
         
+    fn offset(pixel: unscaled::Inner, by: i32) -> unscaled::Inner {
+        (pixel as i32 + by).clamp(0, unscaled::Inner::MAX as i32) as unscaled::Inner
+    }
+
     pub fn end_frame() {
-        state!().chars.clear();
+        state!().layers.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_level() -> common::State {
+        let id = LevelId { world: 0, level: 0 };
+
+        campaign::new_state(xs::from_seed([1; 16]), Session::default(), id).unwrap()
+    }
+
+    #[test]
+    fn hud_text_counts_the_slide_in_progress() {
+        let mut state = first_level();
+
+        assert_eq!(hud_text(&state).as_deref(), Some("1-1 Out On The Ice   MOVES 0   PAR 1"));
+
+        state.history.in_progress = Some(Move {
+            from: state.player_pos,
+            dir: Motion::Right,
+            to: state.player_pos,
+            cells_before: state.cells.clone(),
+            cells_after: state.cells.clone(),
+            blocks_before: Vec::new(),
+            blocks_after: Vec::new(),
+        });
+
+        assert_eq!(hud_text(&state).as_deref(), Some("1-1 Out On The Ice   MOVES 1   PAR 1"));
+    }
+
+    #[test]
+    fn hud_text_is_empty_on_the_title_screen() {
+        let state = state_manipulation::new_state(Size::new(16, 16), [1; 16], Session::default());
+
+        assert_eq!(hud_text(&state), None);
+    }
+
+    #[test]
+    fn sfx_between_plays_for_slides_screens_and_unmuting() {
+        let still = Audible {
+            moving: false,
+            screen: (false, None, false),
+            sound: true,
+        };
+        let moving = Audible { moving: true, ..still };
+        let title = Audible { screen: (true, None, false), ..still };
+        let muted = Audible { sound: false, ..still };
+
+        assert_eq!(sfx_between(still, still), vec![]);
+        assert_eq!(sfx_between(still, moving), vec![SFX::CardSlide]);
+        assert_eq!(sfx_between(moving, still), vec![SFX::CardPlace]);
+        assert_eq!(sfx_between(moving, title), vec![SFX::ButtonPress]);
+        assert_eq!(sfx_between(muted, still), vec![SFX::ButtonPress]);
+        assert_eq!(sfx_between(still, muted), vec![]);
+    }
+
+    #[test]
+    fn board_layout_fills_and_centres_the_board_area() {
+        assert_eq!(platform::board_layout(Size::new(16, 16)), (SPRITE_TILE_SIZE, 0, 0));
+
+        let (tile_size, left, top) = platform::board_layout(Size::new(8, 4));
+        assert_eq!(tile_size * 8, BOARD_AREA_W);
+        assert_eq!((left, top), (0, (BOARD_AREA_H - tile_size * 4) / 2));
+    }
+
+    #[test]
+    fn cell_at_gives_the_cell_under_a_point_even_off_the_board() {
+        let size = Size::new(8, 4);
+        let (tile_size, left, top) = platform::board_layout(size);
+        let at = |x, y| platform::cell_at(size, unscaled::XY { x: unscaled::X(x), y: unscaled::Y(y) });
+
+        assert_eq!(at(left, top), Point { x: 0, y: 0 });
+        assert_eq!(at(left + tile_size * 3 + tile_size / 2, top + tile_size - 1), Point { x: 3, y: 0 });
+        assert_eq!(at(left, top + tile_size * 4), Point { x: 0, y: 4 });
+        assert_eq!(at(left, top - 1), Point { x: 0, y: -1 });
     }
 }
@@ -1,6 +1,6 @@
 use models::{Card, Rank, Suit, get_rank, get_suit, suits};
 
-use platform_types::{Command, PALETTE, sprite, unscaled, command::{self, Rect}, PaletteIndex, FONT_BASE_Y, FONT_WIDTH};
+use platform_types::{ARGB, Command, PALETTE, sprite, unscaled, command::{self, Rect}, PaletteIndex, FONT_BASE_Y, FONT_WIDTH};
 
 #[derive(Default)]
 pub struct Commands {
@@ -20,12 +20,37 @@ impl Commands {
         &mut self,
         sprite_xy: sprite::XY,
         rect: command::Rect,
+    ) {
+        let unscaled::Rect { w, h, .. } = rect.unscaled();
+
+        self.sspr_scaled(sprite_xy, sprite::WH { w, h }, rect);
+    }
+
+    /// Like `sspr`, but the sprite is scaled to fit `rect`.
+    pub fn sspr_scaled(
+        &mut self,
+        sprite_xy: sprite::XY,
+        sprite_wh: sprite::WH,
+        rect: command::Rect,
+    ) {
+        self.sspr_scaled_override(sprite_xy, sprite_wh, rect, 0);
+    }
+
+    /// Like `sspr_scaled`, but the sprite's fully opaque pixels are all drawn
+    /// as `colour_override` instead, unless it is 0.
+    pub fn sspr_scaled_override(
+        &mut self,
+        sprite_xy: sprite::XY,
+        sprite_wh: sprite::WH,
+        rect: command::Rect,
+        colour_override: ARGB,
     ) {
         self.commands.push(
             Command {
                 sprite_xy,
+                sprite_wh,
                 rect,
-                colour_override: 0,
+                colour_override,
             }
         );
     }
@@ -37,27 +62,14 @@ impl Commands {
         y: unscaled::Y,
         colour: PaletteIndex
     ) {
-        fn get_char_xy(sprite_number: u8) -> sprite::XY {
-            type Inner = sprite::Inner;
-            let sprite_number = Inner::from(sprite_number);
-            const CH_SIZE: Inner = CHAR_SIZE as Inner;
-            const SPRITES_PER_ROW: Inner = FONT_WIDTH as Inner / CH_SIZE;
-        
-            sprite::XY {
-                x: sprite::X(
-                    (sprite_number % SPRITES_PER_ROW) * CH_SIZE
-                ),
-                y: sprite::Y(
-                    FONT_BASE_Y as Inner + 
-                    (sprite_number / SPRITES_PER_ROW) * CH_SIZE
-                ),
-            }
-        }
-
         let sprite_xy = get_char_xy(character);
         self.commands.push(
             Command {
                 sprite_xy,
+                sprite_wh: sprite::WH {
+                    w: CHAR_W,
+                    h: CHAR_H,
+                },
                 rect: Rect::from_unscaled(unscaled::Rect {
                     x,
                     y,
@@ -69,6 +81,19 @@ impl Commands {
         );
     }
 
+    pub fn print_line(
+        &mut self,
+        bytes: &[u8],
+        mut x: unscaled::X,
+        y: unscaled::Y,
+        colour: PaletteIndex
+    ) {
+        for &character in bytes {
+            self.print_char(character, x, y, colour);
+            x += CHAR_W;
+        }
+    }
+
     pub fn draw_card(
         &mut self,
         card: Card,
@@ -200,6 +225,25 @@ pub fn get_rank_char_from_rank(rank: Rank) -> u8 {
     }
 }
 
+/// Where in the sprite sheet the font's `CHAR_W` by `CHAR_H` sprite for
+/// `sprite_number` is.
+pub fn get_char_xy(sprite_number: u8) -> sprite::XY {
+    type Inner = sprite::Inner;
+    let sprite_number = Inner::from(sprite_number);
+    const CH_SIZE: Inner = CHAR_SIZE as Inner;
+    const SPRITES_PER_ROW: Inner = FONT_WIDTH as Inner / CH_SIZE;
+
+    sprite::XY {
+        x: sprite::X(
+            (sprite_number % SPRITES_PER_ROW) * CH_SIZE
+        ),
+        y: sprite::Y(
+            FONT_BASE_Y as Inner + 
+            (sprite_number / SPRITES_PER_ROW) * CH_SIZE
+        ),
+    }
+}
+
 pub const CHAR_SIZE: u8 = 8;
 pub const CHAR_W: unscaled::W = unscaled::W(CHAR_SIZE as _);
 pub const CHAR_H: unscaled::H = unscaled::H(CHAR_SIZE as _);
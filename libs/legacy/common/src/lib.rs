@@ -1,8 +1,8 @@
-extern crate rand;
+extern crate xs;
 
 use std::fmt;
-use std::collections::HashMap;
-use rand::{StdRng, Rand, Rng};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use xs::Xs;
 
 use std::iter::Iterator;
 use std::iter::Cycle;
@@ -23,29 +23,201 @@ pub struct Platform {
     pub get_background: fn() -> (Color),
     pub set_layer: fn(i32),
     pub get_layer: fn() -> i32,
+    /// Shows `s` somewhere the player can read it, like a terminal or the
+    /// browser's console.
+    pub log: fn(&str),
 }
 
 pub struct State {
+    /// The board's width and height, in cells. Each level can have its own.
+    pub size: Size,
     pub player_pos: (i32, i32),
     pub initial_player_pos: (i32, i32),
     pub cells: Cells,
-    pub rng: StdRng,
+    /// Where the pushable blocks are. Unlike `cells`, these move around.
+    pub blocks: Vec<(i32, i32)>,
+    pub rng: Xs,
     pub title_screen: bool,
     pub frame_count: u32,
     pub motion: Motion,
+    /// Where the player is between `player_pos` and the next cell over.
+    pub slide: Slide,
     pub max_steps: u8,
+    /// The fewest moves the level can be solved in, if known.
+    pub par: Option<u32>,
+    pub editor: Option<Editor>,
+    pub history: History,
+    pub hint: Option<Hint>,
+    pub hints_used: u32,
+    pub session: Session,
+    pub daily: Option<Daily>,
+    pub campaign_level: Option<LevelId>,
+    pub level_select: Option<LevelSelect>,
+}
+
+/// What lasts for the whole session, rather than for one level or mode.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Session {
+    pub today: Date,
+    pub progress: Progress,
+    pub settings: Settings,
+}
+
+/// Which of the campaign's levels have been completed, and how well.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Progress {
+    pub completed: BTreeSet<LevelId>,
+    /// The fewest moves each completed level has been solved in.
+    pub best: BTreeMap<LevelId, u32>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub sound: bool,
+    pub slide: SlideSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            sound: true,
+            slide: SlideSettings::default(),
+        }
+    }
+}
+
+/// How the player moves from cell to cell.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SlideSettings {
+    /// The fastest the player slides, in tiles per second.
+    pub speed: f32,
+    /// How quickly the player gets up to `speed`, in tiles per second, per
+    /// second. `None` means starting out at full speed.
+    pub acceleration: Option<f32>,
+    /// Whether the player slows down just before stopping.
+    pub ease_out: bool,
+}
+
+impl Default for SlideSettings {
+    fn default() -> Self {
+        SlideSettings {
+            speed: 24.0,
+            acceleration: Some(96.0),
+            ease_out: true,
+        }
+    }
+}
+
+/// The part of the player's movement that is finer than a whole cell.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Slide {
+    /// How far past `player_pos`, in the direction of `motion`, the player
+    /// is, as a fraction of a cell.
+    pub progress: f32,
+    /// In tiles per second.
+    pub speed: f32,
+    /// Which way the player last hit a wall, and how many frames are left of
+    /// bumping into it.
+    pub bump: Option<(Motion, u8)>,
+}
+
+/// A campaign level, by where it is in the campaign. Both fields start at 0.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LevelId {
+    pub world: usize,
+    pub level: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LevelSelect {
+    pub cursor: LevelId,
+}
+
+/// A calendar day, in UTC.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// The puzzle everyone playing on `date` gets.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Daily {
+    pub date: Date,
+    /// The fewest moves the player has reached the goal in, if they have.
+    pub best: Option<u32>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Hint {
+    Move(Motion),
+    /// The goal cannot be reached from where the player is.
+    Stuck,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Editor {
+    pub cursor: (i32, i32),
+    pub playtesting: bool,
 }
 
 pub type Cells = HashMap<(i32, i32), Cell>;
 
-#[derive(Copy, Clone)]
+/// A completed slide. The board is kept from both before and after the slide,
+/// so slides that change the board can be undone and redone too.
+#[derive(Clone, Debug)]
+pub struct Move {
+    pub from: (i32, i32),
+    pub dir: Motion,
+    pub to: (i32, i32),
+    pub cells_before: Cells,
+    pub cells_after: Cells,
+    pub blocks_before: Vec<(i32, i32)>,
+    pub blocks_after: Vec<(i32, i32)>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    pub undo: Vec<Move>,
+    pub redo: Vec<Move>,
+    /// The slide the player is currently in the middle of, if any.
+    pub in_progress: Option<Move>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
 pub enum Cell {
     Wall,
     Goal,
+    /// Ground that stops a player sliding onto it, without blocking them like
+    /// a `Wall` does.
+    Rough,
+    /// Floor that sends a player sliding onto it off in the direction it
+    /// points, and that cannot be slid onto against that direction.
+    /// `Arrow(Stopped)` is plain ice.
+    Arrow(Motion),
+    /// A player sliding onto one of these comes out of the other one with the
+    /// same number, still sliding the same way.
+    Teleporter(u8),
+    /// Ice that breaks into a `Hole` once the player slides off it.
+    Cracked,
+    /// Sliding into one of these fails the level.
+    Hole,
+    /// Picked up by sliding over it, which opens every `Door` of the same
+    /// colour. The `Goal`s do nothing until every key has been picked up.
+    Key(u8),
+    /// A `Wall` until a `Key` of the same colour is picked up.
+    Door(u8),
 }
 use Cell::*;
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Motion {
     Stopped,
     Up,
@@ -59,6 +231,17 @@ impl Cell {
         match self {
             Wall => "☒",
             Goal => "\u{E010}",
+            Rough => "▒",
+            Arrow(Motion::Up) => "↑",
+            Arrow(Motion::Right) => "→",
+            Arrow(Motion::Down) => "↓",
+            Arrow(Motion::Left) => "←",
+            Arrow(Motion::Stopped) => "☐",
+            Teleporter(_) => "\u{E018}",
+            Cracked => "░",
+            Hole => "●",
+            Key(_) => "⚷",
+            Door(_) => "▦",
         }
     }
 }
@@ -68,6 +251,8 @@ impl ToString for Cell {
         match *self {
             Wall => "☒".to_string(),
             Goal => "\u{E010}".to_string(),
+            Rough => "▒".to_string(),
+            Arrow(_) | Teleporter(_) | Cracked | Hole | Key(_) | Door(_) => self.to_static_str().to_string(),
         }
     }
 }
@@ -462,6 +647,7 @@ pub enum Event {
     ControlReleased,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Color {
     pub red: u8,
     pub green: u8,
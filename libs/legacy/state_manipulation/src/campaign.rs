@@ -0,0 +1,155 @@
+//! The handmade levels, grouped into worlds. The level files are embedded in
+//! the binary, so they are available on every platform.
+
+use common::*;
+
+use xs::Xs;
+
+use level_format::{self, Level};
+
+pub struct World {
+    pub name: &'static str,
+    /// Level files, in the `level_format` format, in the order they are played.
+    pub levels: &'static [&'static str],
+}
+
+pub const WORLDS: &[World] = &[
+    World {
+        name: "First Steps",
+        levels: &[
+            include_str!("../levels/1-1.txt"),
+            include_str!("../levels/1-2.txt"),
+            include_str!("../levels/1-3.txt"),
+            include_str!("../levels/1-4.txt"),
+        ],
+    },
+    World {
+        name: "Pinball",
+        levels: &[
+            include_str!("../levels/2-1.txt"),
+            include_str!("../levels/2-2.txt"),
+            include_str!("../levels/2-3.txt"),
+            include_str!("../levels/2-4.txt"),
+        ],
+    },
+    World {
+        name: "The Long Way Round",
+        levels: &[
+            include_str!("../levels/3-1.txt"),
+            include_str!("../levels/3-2.txt"),
+            include_str!("../levels/3-3.txt"),
+            include_str!("../levels/3-4.txt"),
+        ],
+    },
+];
+
+/// Panics if the level file is malformed, which the tests check for.
+pub fn level(id: LevelId) -> Option<Level> {
+    let text = WORLDS.get(id.world)?.levels.get(id.level)?;
+
+    match level_format::parse(text) {
+        Ok(level) => Some(level),
+        Err(e) => panic!("campaign level {}-{}: {}", id.world + 1, id.level + 1, e),
+    }
+}
+
+pub fn ids() -> impl Iterator<Item = LevelId> {
+    WORLDS.iter().enumerate().flat_map(|(world, w)| {
+        (0..w.levels.len()).map(move |level| {
+            LevelId {
+                world: world,
+                level: level,
+            }
+        })
+    })
+}
+
+pub fn next(id: LevelId) -> Option<LevelId> {
+    ids().skip_while(|&other| other != id).nth(1)
+}
+
+/// A level is unlocked once the one before it is completed.
+pub fn is_unlocked(progress: &Progress, id: LevelId) -> bool {
+    let mut previous = None;
+    for other in ids() {
+        if other == id {
+            break;
+        }
+        previous = Some(other);
+    }
+
+    match previous {
+        Some(previous) => progress.completed.contains(&previous) || progress.completed.contains(&id),
+        None => true,
+    }
+}
+
+/// Where the level select cursor should start: the first level not yet
+/// completed, or the last level if they all have been.
+pub fn first_incomplete(progress: &Progress) -> LevelId {
+    ids()
+        .find(|id| !progress.completed.contains(id))
+        .or_else(|| ids().last())
+        .unwrap_or(LevelId {
+            world: 0,
+            level: 0,
+        })
+}
+
+pub fn new_state(rng: Xs, session: Session, id: LevelId) -> Option<State> {
+    let mut state = level(id)?.to_state(rng, session);
+
+    state.campaign_level = Some(id);
+
+    Some(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solver;
+    use MAX_BOARD_SIDE;
+
+    #[test]
+    fn every_level_parses_and_is_solvable() {
+        for id in ids() {
+            let level = level(id).unwrap();
+
+            assert!(level.size.width >= 1 && level.size.width <= MAX_BOARD_SIDE,
+                    "{:?}",
+                    id);
+            assert!(level.size.height >= 1 && level.size.height <= MAX_BOARD_SIDE,
+                    "{:?}",
+                    id);
+            assert!(solver::solve(level.size, &level.cells, &level.blocks, level.start).is_some(),
+                    "{:?}",
+                    id);
+        }
+    }
+
+    #[test]
+    fn completing_a_level_unlocks_the_next_one_even_across_worlds() {
+        let mut progress = Progress::default();
+        let last_of_first_world = LevelId {
+            world: 0,
+            level: WORLDS[0].levels.len() - 1,
+        };
+        let first_of_second_world = next(last_of_first_world).unwrap();
+
+        assert_eq!(first_of_second_world,
+                   LevelId {
+                       world: 1,
+                       level: 0,
+                   });
+        assert!(!is_unlocked(&progress, first_of_second_world));
+
+        progress.completed.insert(last_of_first_world);
+
+        assert!(is_unlocked(&progress, first_of_second_world));
+        assert_eq!(first_incomplete(&progress),
+                   LevelId {
+                       world: 0,
+                       level: 0,
+                   });
+    }
+}
@@ -0,0 +1,147 @@
+//! A puzzle that everyone playing on the same (UTC) day gets, so results can
+//! be compared.
+
+use common::*;
+
+use xs::Seed;
+
+use super::{history, next_level, random_level_size};
+
+/// The daily puzzle is a bit harder than the first few randomly generated
+/// levels, but always the same difficulty.
+const MAX_STEPS: u8 = 8;
+
+pub fn new_state(session: Session) -> State {
+    let today = session.today;
+    let mut state = next_level(random_level_size(MAX_STEPS),
+                               xs::from_seed(seed(today)),
+                               MAX_STEPS,
+                               session);
+
+    state.daily = Some(Daily {
+        date: today,
+        best: None,
+    });
+
+    state
+}
+
+pub fn reached_goal(platform: &Platform, state: &mut State) {
+    let mut daily = match state.daily {
+        Some(daily) => daily,
+        None => return,
+    };
+
+    //the winning slide ends on the goal, rather than going on past it, and
+    //is counted. Moves cannot be started from the goal, so the run is over
+    state.motion = Motion::Stopped;
+    state.slide = Slide::default();
+    history::finish(state);
+
+    let moves = state.history.undo.len() as u32;
+
+    if daily.best.map(|best| moves < best).unwrap_or(true) {
+        daily.best = Some(moves);
+
+        //logged so it can be pasted somewhere, to compare with others
+        let solution: String = state.history
+            .undo
+            .iter()
+            .map(|slide| super::hint_string(Hint::Move(slide.dir)))
+            .collect();
+
+        let result = match state.par {
+            Some(par) => format!("daily {}: {} moves (par {}) {}", daily.date, moves, par, solution),
+            None => format!("daily {}: {} moves {}", daily.date, moves, solution),
+        };
+
+        (platform.log)(&result);
+    }
+
+    state.daily = Some(daily);
+}
+
+fn seed(date: Date) -> Seed {
+    //splitmix64, so nearby days do not get similar seeds
+    fn next(x: &mut u64) -> u64 {
+        *x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = *x;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    let mut x = (date.year as u64) << 16 | (date.month as u64) << 8 | date.day as u64;
+
+    let mut seed = [0; 16];
+    seed[..8].copy_from_slice(&next(&mut x).to_le_bytes());
+    seed[8..].copy_from_slice(&next(&mut x).to_le_bytes());
+
+    seed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::Cell::*;
+    use std::cell::RefCell;
+    use tests::{headless_platform, press};
+
+    fn on(year: u16, month: u8, day: u8) -> Session {
+        Session {
+            today: Date {
+                year: year,
+                month: month,
+                day: day,
+            },
+            ..Session::default()
+        }
+    }
+
+    #[test]
+    fn everyone_gets_the_same_board_on_the_same_day() {
+        let a = new_state(on(2026, 10, 17));
+        let b = new_state(on(2026, 10, 17));
+        let c = new_state(on(2026, 10, 18));
+
+        assert_eq!((a.player_pos, &a.cells), (b.player_pos, &b.cells));
+        assert_ne!((a.player_pos, &a.cells), (c.player_pos, &c.cells));
+        assert_eq!(a.par, Some(MAX_STEPS as u32));
+    }
+
+    thread_local! {
+        static LOGGED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record(s: &str) {
+        LOGGED.with(|logged| logged.borrow_mut().push(s.to_owned()));
+    }
+
+    #[test]
+    fn the_winning_slide_stops_on_the_goal_and_is_in_the_result() {
+        // S . G . . so one slide right, which would go on past the goal
+        let mut state = new_state(on(2026, 10, 17));
+        state.cells.clear();
+        state.blocks.clear();
+        state.player_pos = (0, 0);
+        state.initial_player_pos = (0, 0);
+        state.cells.insert((2, 0), Goal);
+        state.par = Some(1);
+
+        let platform = Platform { log: record, ..headless_platform() };
+
+        press(&platform, &mut state, KeyCode::Right);
+
+        assert_eq!(state.player_pos, (2, 0));
+        assert_eq!(state.daily.and_then(|daily| daily.best), Some(1));
+
+        //and the run is over, so nothing more happens
+        press(&platform, &mut state, KeyCode::Down);
+
+        assert_eq!(state.player_pos, (2, 0));
+        assert_eq!(state.history.undo.len(), 1);
+        LOGGED.with(|logged| {
+            assert_eq!(*logged.borrow(), vec!["daily 2026-10-17: 1 moves (par 1) →"]);
+        });
+    }
+}
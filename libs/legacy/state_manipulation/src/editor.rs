@@ -0,0 +1,296 @@
+//! A mode for building levels on the board itself, and play-testing them.
+//!
+//! While editing:
+//!
+//! * The arrow keys, (or WASD,) move the cursor.
+//! * `R` cycles the cell under the cursor between empty, `Wall`, `Goal`,
+//!   `Rough`, each direction of `Arrow`, `Teleporter`s 0 to 2, `Cracked`,
+//!   `Hole`, a `Key` then a `Door` of each of three colours, and a block.
+//!   Each teleporter needs a partner with the same number to do anything,
+//!   and the level cannot be exported until it has one.
+//! * `X` moves the start to the cursor, clearing whatever was there. The start
+//!   always stays on empty ice, so `R` does nothing under it.
+//! * `Tab` logs the level, in the `level_format` format, so it can be copied
+//!   out of the terminal, or the browser's console.
+//! * `Enter` switches between editing and play-testing.
+//! * Clicking a cell moves the cursor to it, and clicking the cell under the
+//!   cursor does what `R` does.
+//!
+//! While play-testing, the keys are the same as when playing a level, except
+//! that `Enter` goes back to editing.
+
+use common::*;
+use common::Cell::*;
+use common::Motion::*;
+
+use std::collections::HashMap;
+
+use xs::Xs;
+
+use history;
+use level_format::Level;
+use solver;
+
+use super::{add, cross_mode_event_handling, dir_to_tuple, goal_is_active, history_event_handling,
+            move_player, new_state as new_title_state, print_cell, print_tuple, reached_goal,
+            BLOCK_STR};
+
+pub fn new_state(size: Size, rng: Xs, session: Session) -> State {
+    let start = (size.width / 2, size.height / 2);
+
+    State {
+        size: size,
+        player_pos: start,
+        initial_player_pos: start,
+        cells: HashMap::new(),
+        blocks: Vec::new(),
+        rng: rng,
+        title_screen: false,
+        frame_count: 0,
+        motion: Stopped,
+        slide: Slide::default(),
+        max_steps: 4,
+        par: None,
+        editor: Some(Editor {
+            cursor: start,
+            playtesting: false,
+        }),
+        history: History::default(),
+        hint: None,
+        hints_used: 0,
+        session: session,
+        daily: None,
+        campaign_level: None,
+        level_select: None,
+    }
+}
+
+//returns true if quit requested
+pub fn update_and_render(platform: &Platform,
+                         state: &mut State,
+                         events: &mut Vec<Event>,
+                         dt: f32)
+                         -> bool {
+    for event in events {
+        match *event {
+            Event::Close |
+            Event::KeyPressed { key: KeyCode::Escape, ctrl: _, shift: _ } => return true,
+            Event::KeyPressed { key: KeyCode::Enter, ctrl: _, shift: _ } => {
+                toggle_playtesting(state);
+            }
+            _ => {
+                if is_playtesting(state) {
+                    cross_mode_event_handling(platform, state, event);
+                    history_event_handling(state, event);
+                } else {
+                    editing_event_handling(platform, state, event);
+                }
+            }
+        }
+
+        if state.editor.is_none() {
+            //we were reset back to the title screen
+            return false;
+        }
+    }
+
+    if is_playtesting(state) {
+        move_player(state.size, state, dt);
+
+        if reached_goal(state) {
+            toggle_playtesting(state);
+        }
+
+        super::draw(platform, state);
+    } else {
+        draw(platform, state);
+    }
+
+    false
+}
+
+fn is_playtesting(state: &State) -> bool {
+    state.editor.map(|editor| editor.playtesting).unwrap_or(false)
+}
+
+fn toggle_playtesting(state: &mut State) {
+    if let Some(ref mut editor) = state.editor {
+        editor.playtesting = !editor.playtesting;
+    }
+
+    //puts back any blocks that were pushed, as well as the player
+    history::reset(state);
+}
+
+fn editing_event_handling(platform: &Platform, state: &mut State, event: &Event) {
+    let size = state.size;
+
+    let mut editor = match state.editor {
+        Some(editor) => editor,
+        None => return,
+    };
+
+    match *event {
+        Event::KeyPressed { key: KeyCode::W, ctrl: _, shift: _ } |
+        Event::KeyPressed { key: KeyCode::Up, ctrl: _, shift: _ } => {
+            editor.cursor = move_cursor(size, editor.cursor, Up);
+        }
+        Event::KeyPressed { key: KeyCode::D, ctrl: _, shift: _ } |
+        Event::KeyPressed { key: KeyCode::Right, ctrl: _, shift: _ } => {
+            editor.cursor = move_cursor(size, editor.cursor, Right);
+        }
+        Event::KeyPressed { key: KeyCode::S, ctrl: _, shift: _ } |
+        Event::KeyPressed { key: KeyCode::Down, ctrl: _, shift: _ } => {
+            editor.cursor = move_cursor(size, editor.cursor, Down);
+        }
+        Event::KeyPressed { key: KeyCode::A, ctrl: _, shift: _ } |
+        Event::KeyPressed { key: KeyCode::Left, ctrl: _, shift: _ } => {
+            editor.cursor = move_cursor(size, editor.cursor, Left);
+        }
+        Event::KeyPressed { key: KeyCode::R, ctrl: false, shift: _ } => {
+            cycle_cell(state, editor.cursor);
+        }
+        Event::KeyPressed { key: KeyCode::R, ctrl: true, shift: _ } => {
+            *state = new_title_state((platform.size)(),
+                                     xs::new_seed(&mut state.rng),
+                                     state.session.clone());
+            return;
+        }
+        Event::KeyPressed { key: KeyCode::X, ctrl: _, shift: _ } => {
+            state.cells.remove(&editor.cursor);
+            state.blocks.retain(|&block| block != editor.cursor);
+            state.initial_player_pos = editor.cursor;
+            state.player_pos = editor.cursor;
+        }
+        Event::KeyPressed { key: KeyCode::Tab, ctrl: _, shift: _ } => {
+            export(platform, state);
+        }
+        Event::KeyPressed { key: KeyCode::MouseLeft, ctrl: _, shift: _ } => {
+            let mouse = (platform.mouse_position)();
+            let clicked = (mouse.x, mouse.y);
+
+            if clicked == editor.cursor {
+                cycle_cell(state, editor.cursor);
+            } else if mouse.x >= 0 && mouse.y >= 0 && mouse.x < size.width &&
+                      mouse.y < size.height {
+                editor.cursor = clicked;
+            }
+        }
+        _ => (),
+    }
+
+    state.editor = Some(editor);
+}
+
+fn move_cursor(size: Size, cursor: (i32, i32), dir: Motion) -> (i32, i32) {
+    let (x, y) = add(cursor, dir_to_tuple(dir));
+
+    (std::cmp::min(std::cmp::max(x, 0), size.width - 1),
+     std::cmp::min(std::cmp::max(y, 0), size.height - 1))
+}
+
+/// More than this many is more than anyone can keep track of on one board.
+const EDITOR_TELEPORTER_PAIRS: u8 = 3;
+const EDITOR_KEY_COLOURS: u8 = 3;
+
+fn cycle_cell(state: &mut State, coords: (i32, i32)) {
+    //the start stays on empty ice, so a level can always be exported
+    if coords == state.initial_player_pos {
+        return;
+    }
+
+    if let Some(index) = state.blocks.iter().position(|&block| block == coords) {
+        state.blocks.remove(index);
+        return;
+    }
+
+    let next = match state.cells.get(&coords) {
+        None => Some(Wall),
+        Some(&Wall) => Some(Goal),
+        Some(&Goal) => Some(Rough),
+        Some(&Rough) => Some(Arrow(Up)),
+        Some(&Arrow(Up)) => Some(Arrow(Right)),
+        Some(&Arrow(Right)) => Some(Arrow(Down)),
+        Some(&Arrow(Down)) => Some(Arrow(Left)),
+        Some(&Arrow(Left)) => Some(Teleporter(0)),
+        Some(&Teleporter(pair)) if pair < EDITOR_TELEPORTER_PAIRS - 1 => Some(Teleporter(pair + 1)),
+        Some(&Teleporter(_)) => Some(Cracked),
+        Some(&Cracked) => Some(Hole),
+        Some(&Hole) => Some(Key(0)),
+        Some(&Key(colour)) => Some(Door(colour)),
+        Some(&Door(colour)) if colour < EDITOR_KEY_COLOURS - 1 => Some(Key(colour + 1)),
+        Some(&Door(_)) |
+        Some(&Arrow(Stopped)) => None,
+    };
+
+    match next {
+        Some(cell) => {
+            state.cells.insert(coords, cell);
+        }
+        None => {
+            state.cells.remove(&coords);
+
+            //blocks come after the cells
+            state.blocks.push(coords);
+        }
+    }
+}
+
+fn export(platform: &Platform, state: &State) {
+    let level = match Level::from_state(state) {
+        Ok(level) => level,
+        Err(e) => {
+            (platform.log)(&format!("cannot export: {}", e));
+            return;
+        }
+    };
+
+    let solvable = match solver::solve(state.size,
+                                       &state.cells,
+                                       &state.blocks,
+                                       state.initial_player_pos) {
+        Some(moves) => format!("solvable in {} moves", moves.len()),
+        None => "not solvable".to_string(),
+    };
+
+    (platform.log)(&format!("{}{}", level, solvable));
+}
+
+fn draw(platform: &Platform, state: &State) {
+    let goal_active = goal_is_active(&state.cells);
+    for (&coords, &cell) in state.cells.iter() {
+        print_cell(platform, coords, cell, goal_active, state.frame_count);
+    }
+
+    print_tuple(platform, state.initial_player_pos, "☐");
+
+    for &block in state.blocks.iter() {
+        print_tuple(platform, block, BLOCK_STR);
+    }
+
+    if let Some(editor) = state.editor {
+        //blink, so the cell under the cursor can still be seen
+        if state.frame_count & 16 == 0 {
+            print_tuple(platform, editor.cursor, "@");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycling_the_cell_under_the_start_leaves_it_exportable() {
+        let mut state = new_state(Size::new(4, 4), xs::from_seed([0; 16]), Session::default());
+        let start = state.initial_player_pos;
+
+        for _ in 0..4 {
+            cycle_cell(&mut state, start);
+        }
+
+        assert_eq!(state.cells.get(&start), None);
+        assert!(!state.blocks.contains(&start));
+        assert!(Level::from_state(&state).is_ok());
+    }
+}
@@ -0,0 +1,196 @@
+//! Undo and redo of individual slides.
+
+use common::*;
+use common::Motion::*;
+
+pub fn start(state: &mut State, dir: Motion) {
+    state.history.in_progress = Some(Move {
+        from: state.player_pos,
+        dir: dir,
+        to: state.player_pos,
+        cells_before: state.cells.clone(),
+        cells_after: state.cells.clone(),
+        blocks_before: state.blocks.clone(),
+        blocks_after: state.blocks.clone(),
+    });
+}
+
+pub fn finish(state: &mut State) {
+    if let Some(mut current) = state.history.in_progress.take() {
+        current.to = state.player_pos;
+        current.cells_after = state.cells.clone();
+        current.blocks_after = state.blocks.clone();
+
+        //bumping straight into a wall is not worth undoing
+        if current.from != current.to || current.cells_before != current.cells_after ||
+           current.blocks_before != current.blocks_after {
+            state.history.undo.push(current);
+            state.history.redo.clear();
+        }
+    }
+}
+
+pub fn undo(state: &mut State) {
+    if state.motion != Stopped {
+        return;
+    }
+
+    if let Some(previous) = state.history.undo.pop() {
+        state.hint = None;
+        state.slide = Slide::default();
+        state.player_pos = previous.from;
+        state.cells = previous.cells_before.clone();
+        state.blocks = previous.blocks_before.clone();
+        state.history.redo.push(previous);
+    }
+}
+
+pub fn redo(state: &mut State) {
+    if state.motion != Stopped {
+        return;
+    }
+
+    if let Some(next) = state.history.redo.pop() {
+        state.hint = None;
+        state.slide = Slide::default();
+        state.player_pos = next.to;
+        state.cells = next.cells_after.clone();
+        state.blocks = next.blocks_after.clone();
+        state.history.undo.push(next);
+    }
+}
+
+/// Puts the level back how it was before the first move.
+pub fn reset(state: &mut State) {
+    let history = std::mem::take(&mut state.history);
+
+    let first = history.undo.first().or(history.in_progress.as_ref());
+    if let Some(first) = first {
+        state.cells = first.cells_before.clone();
+        state.blocks = first.blocks_before.clone();
+    }
+
+    state.player_pos = state.initial_player_pos;
+    state.motion = Stopped;
+    state.slide = Slide::default();
+    state.hint = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn state_at(player_pos: (i32, i32)) -> State {
+        State {
+            size: Size::new(16, 16),
+            player_pos: player_pos,
+            initial_player_pos: player_pos,
+            cells: HashMap::new(),
+            blocks: Vec::new(),
+            rng: xs::from_seed([42; 16]),
+            title_screen: false,
+            frame_count: 0,
+            motion: Stopped,
+            slide: Slide::default(),
+            max_steps: 4,
+            par: None,
+            editor: None,
+            history: History::default(),
+            hint: None,
+            hints_used: 0,
+            session: Session::default(),
+            daily: None,
+            campaign_level: None,
+            level_select: None,
+        }
+    }
+
+    fn slide(state: &mut State, dir: Motion, to: (i32, i32)) {
+        state.motion = dir;
+        start(state, dir);
+        state.player_pos = to;
+        state.motion = Stopped;
+        finish(state);
+    }
+
+    #[test]
+    fn undo_then_redo_retraces_these_moves() {
+        let mut state = state_at((0, 0));
+
+        slide(&mut state, Right, (5, 0));
+        slide(&mut state, Down, (5, 7));
+
+        undo(&mut state);
+        assert_eq!(state.player_pos, (5, 0));
+        undo(&mut state);
+        assert_eq!(state.player_pos, (0, 0));
+        undo(&mut state);
+        assert_eq!(state.player_pos, (0, 0));
+
+        redo(&mut state);
+        assert_eq!(state.player_pos, (5, 0));
+        redo(&mut state);
+        assert_eq!(state.player_pos, (5, 7));
+    }
+
+    #[test]
+    fn a_new_move_after_undoing_clears_the_redo_stack() {
+        let mut state = state_at((0, 0));
+
+        slide(&mut state, Right, (5, 0));
+        undo(&mut state);
+        slide(&mut state, Down, (0, 7));
+
+        assert_eq!(state.history.redo.len(), 0);
+
+        redo(&mut state);
+        assert_eq!(state.player_pos, (0, 7));
+    }
+
+    #[test]
+    fn moves_that_go_nowhere_are_not_recorded() {
+        let mut state = state_at((0, 0));
+
+        slide(&mut state, Up, (0, 0));
+
+        assert_eq!(state.history.undo.len(), 0);
+    }
+
+    #[test]
+    fn undo_restores_changes_to_the_board() {
+        let mut state = state_at((0, 0));
+
+        state.motion = Right;
+        start(&mut state, Right);
+        state.player_pos = (3, 0);
+        state.cells.insert((1, 0), Cell::Wall);
+        state.motion = Stopped;
+        finish(&mut state);
+
+        undo(&mut state);
+        assert!(state.cells.is_empty());
+
+        redo(&mut state);
+        assert_eq!(state.cells.get(&(1, 0)), Some(&Cell::Wall));
+    }
+
+    #[test]
+    fn reset_puts_pushed_blocks_back() {
+        let mut state = state_at((0, 0));
+        state.blocks = vec![(2, 0), (0, 4)];
+
+        state.motion = Right;
+        start(&mut state, Right);
+        state.player_pos = (2, 0);
+        state.blocks[0] = (15, 0);
+        state.motion = Stopped;
+        finish(&mut state);
+
+        slide(&mut state, Down, (2, 15));
+
+        reset(&mut state);
+        assert_eq!(state.player_pos, (0, 0));
+        assert_eq!(state.blocks, vec![(2, 0), (0, 4)]);
+    }
+}
@@ -0,0 +1,753 @@
+//! A plain-text format for levels, so they can be written in a text editor.
+//!
+//! A level file is a header of `key: value` lines, then a blank line, then
+//! the board, one line per row. For example:
+//!
+//! ```text
+//! name: Around The Corner
+//! size: 8x4
+//! max_steps: 3
+//!
+//! .....#..
+//! ....G...
+//! ....#...
+//! S.......
+//! ```
+//!
+//! The header keys are:
+//!
+//! * `size`: Required. The board's width and height, as `<width>x<height>`.
+//!   Neither can be more than 32.
+//! * `name`: Optional. Anything up to the end of the line.
+//! * `max_steps`: Optional, defaulting to 4. Copied into the `State` that
+//!   `Level::to_state` makes. Campaign levels and the editor ignore it, but
+//!   a state played outside of those is followed by randomly generated
+//!   levels, starting from one more than this.
+//!
+//! The board must have exactly as many rows and columns as `size` says, made
+//! of these characters:
+//!
+//! * `.`: Empty ice.
+//! * `#`: A wall.
+//! * `G`: A goal.
+//! * `,`: Rough ground.
+//! * `O`: A pushable block, on plain ice.
+//! * `^`, `>`, `v`, `<`: An arrow, pointing up, right, down or left.
+//! * `~`: Cracked ice, which breaks into a hole once slid off of.
+//! * `_`: A hole.
+//! * `a` to `e`: A key. Picking one up opens all the doors of its colour,
+//!   and the goal only counts once every key has been picked up.
+//! * `A` to `E`: A door, opened by the key with the same letter.
+//! * `0` to `9`: A teleporter. Each digit used must appear exactly twice,
+//!   once for each end of the pair.
+//! * `S`: Where the player starts. There must be exactly one of these.
+//!
+//! Since each place on the board gets one character, the start and the
+//! blocks can only be on empty ice. `Level::check` says whether a level can
+//! be written in this format.
+
+use common::*;
+use common::Cell::*;
+use common::Motion::*;
+
+use std::collections::HashMap;
+use std::fmt;
+
+use xs::Xs;
+
+use solver;
+use MAX_BOARD_SIDE;
+
+const DEFAULT_MAX_STEPS: u8 = 4;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Level {
+    pub name: String,
+    pub size: Size,
+    pub start: (i32, i32),
+    pub cells: Cells,
+    pub blocks: Vec<(i32, i32)>,
+    pub max_steps: u8,
+}
+
+impl Level {
+    /// Fails if the state's level cannot be written in this format.
+    pub fn from_state(state: &State) -> Result<Level, WriteError> {
+        let level = Level {
+            name: String::new(),
+            size: state.size,
+            start: state.initial_player_pos,
+            cells: state.cells.clone(),
+            blocks: state.blocks.clone(),
+            max_steps: state.max_steps,
+        };
+
+        level.check()?;
+
+        Ok(level)
+    }
+
+    /// Returns why the level cannot be written in this format, if it cannot.
+    /// Writing out a level this accepts, then parsing it, gives back the same
+    /// level, as long as the blocks are in the order they are on the board.
+    pub fn check(&self) -> Result<(), WriteError> {
+        let in_bounds = |(x, y): (i32, i32)| {
+            x >= 0 && y >= 0 && x < self.size.width && y < self.size.height
+        };
+
+        if !in_bounds(self.start) {
+            return Err(WriteError::OutOfBounds(self.start));
+        }
+
+        //sorted so the same level always gives the same error
+        let mut cells: Vec<((i32, i32), Cell)> = self.cells
+            .iter()
+            .map(|(&coords, &cell)| (coords, cell))
+            .collect();
+        cells.sort_by_key(|&((x, y), _)| (y, x));
+
+        let mut teleporters: HashMap<u8, u8> = HashMap::new();
+        for &(coords, cell) in cells.iter() {
+            if !in_bounds(coords) {
+                return Err(WriteError::OutOfBounds(coords));
+            }
+            if coords == self.start {
+                return Err(WriteError::Stacked(coords));
+            }
+            if cell_char(cell).is_none() {
+                return Err(WriteError::NoCharacter(coords, cell));
+            }
+            if let Teleporter(pair) = cell {
+                *teleporters.entry(pair).or_insert(0) += 1;
+            }
+        }
+
+        for (i, &block) in self.blocks.iter().enumerate() {
+            if !in_bounds(block) {
+                return Err(WriteError::OutOfBounds(block));
+            }
+            if block == self.start || self.cells.contains_key(&block) ||
+               self.blocks[..i].contains(&block) {
+                return Err(WriteError::Stacked(block));
+            }
+        }
+
+        let mut unpaired: Vec<u8> = teleporters.into_iter()
+            .filter(|&(_, count)| count != 2)
+            .map(|(pair, _)| pair)
+            .collect();
+        unpaired.sort();
+        if let Some(&pair) = unpaired.first() {
+            return Err(WriteError::UnpairedTeleporter(pair));
+        }
+
+        Ok(())
+    }
+
+    pub fn to_state(&self, rng: Xs, session: Session) -> State {
+        State {
+            size: self.size,
+            player_pos: self.start,
+            initial_player_pos: self.start,
+            cells: self.cells.clone(),
+            blocks: self.blocks.clone(),
+            rng: rng,
+            title_screen: false,
+            frame_count: 0,
+            motion: Stopped,
+            slide: Slide::default(),
+            max_steps: self.max_steps,
+            par: solver::solve(self.size, &self.cells, &self.blocks, self.start)
+                .map(|moves| moves.len() as u32),
+            editor: None,
+            history: History::default(),
+            hint: None,
+            hints_used: 0,
+            session: session,
+            daily: None,
+            campaign_level: None,
+            level_select: None,
+        }
+    }
+}
+
+/// The character for `cell`, if there is one.
+fn cell_char(cell: Cell) -> Option<char> {
+    Some(match cell {
+        Wall => '#',
+        Goal => 'G',
+        Rough => ',',
+        Arrow(Up) => '^',
+        Arrow(Right) => '>',
+        Arrow(Down) => 'v',
+        Arrow(Left) => '<',
+        Arrow(Stopped) => return None,
+        Teleporter(pair) if pair <= 9 => (b'0' + pair) as char,
+        Teleporter(_) => return None,
+        Cracked => '~',
+        Hole => '_',
+        Key(colour) if colour < COLOURS => (b'a' + colour) as char,
+        Door(colour) if colour < COLOURS => (b'A' + colour) as char,
+        Key(_) | Door(_) => return None,
+    })
+}
+
+/// How many colours of key and door there are letters for.
+const COLOURS: u8 = 5;
+
+/// Only levels that `Level::check` accepts are written faithfully. Anything
+/// else loses whatever has no character, or is underneath something else.
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.name.is_empty() {
+            writeln!(f, "name: {}", self.name)?;
+        }
+        writeln!(f, "size: {}", self.size)?;
+        writeln!(f, "max_steps: {}", self.max_steps)?;
+        writeln!(f)?;
+
+        for y in 0..self.size.height {
+            for x in 0..self.size.width {
+                let c = if (x, y) == self.start {
+                    'S'
+                } else if self.blocks.contains(&(x, y)) {
+                    'O'
+                } else {
+                    self.cells.get(&(x, y)).and_then(|&cell| cell_char(cell)).unwrap_or('.')
+                };
+
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ErrorKind {
+    MissingSeparator,
+    MalformedHeaderLine,
+    UnknownKey(String),
+    DuplicateKey(String),
+    BadSize(String),
+    BadMaxSteps(String),
+    MissingSize,
+    UnexpectedChar(char),
+    WrongRowLength { expected: i32, found: i32 },
+    TooFewRows { expected: i32, found: i32 },
+    TooManyRows,
+    MissingStart,
+    MultipleStarts,
+    UnpairedTeleporter(u8),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::ErrorKind::*;
+        match *self {
+            MissingSeparator => write!(f, "expected a blank line between the header and the board"),
+            MalformedHeaderLine => write!(f, "expected a header line like `key: value`"),
+            UnknownKey(ref key) => write!(f, "unknown header key `{}`", key),
+            DuplicateKey(ref key) => write!(f, "header key `{}` appears more than once", key),
+            BadSize(ref value) => {
+                write!(f,
+                       "expected a size like `16x16`, up to `{0}x{0}`, found `{1}`",
+                       MAX_BOARD_SIDE,
+                       value)
+            }
+            BadMaxSteps(ref value) => write!(f, "expected a number from 0 to 255, found `{}`", value),
+            MissingSize => write!(f, "the header must include a `size`"),
+            UnexpectedChar(c) => write!(f, "unexpected character `{}`", c),
+            WrongRowLength { expected, found } => {
+                write!(f, "expected a row of {} cells, found {}", expected, found)
+            }
+            TooFewRows { expected, found } => {
+                write!(f, "expected {} rows, found {}", expected, found)
+            }
+            TooManyRows => write!(f, "more rows than the header's `size` allows"),
+            MissingStart => write!(f, "the board must have a start, marked with `S`"),
+            MultipleStarts => write!(f, "the board must have only one start"),
+            UnpairedTeleporter(pair) => {
+                write!(f, "teleporter `{}` must appear exactly twice", pair)
+            }
+        }
+    }
+}
+
+/// Why a `Level` cannot be written in this format.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WriteError {
+    OutOfBounds((i32, i32)),
+    /// The start, a block, or a cell share this place, which can only have
+    /// one character.
+    Stacked((i32, i32)),
+    /// There is no character for this cell, like a teleporter numbered
+    /// past 9.
+    NoCharacter((i32, i32), Cell),
+    UnpairedTeleporter(u8),
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::WriteError::*;
+        match *self {
+            OutOfBounds((x, y)) => write!(f, "({}, {}) is outside the board", x, y),
+            Stacked((x, y)) => {
+                write!(f,
+                       "more than one thing is at ({}, {}), but the start and blocks can \
+                        only go on empty ice",
+                       x,
+                       y)
+            }
+            NoCharacter((x, y), cell) => {
+                write!(f, "there is no character for the {:?} at ({}, {})", cell, x, y)
+            }
+            UnpairedTeleporter(pair) => {
+                write!(f, "teleporter `{}` must appear exactly twice", pair)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WriteError {}
+
+/// `line` and `column` are both 1-based, and `column` counts `char`s.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn parse(text: &str) -> Result<Level, ParseError> {
+    let mut lines = text.lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .enumerate()
+        .map(|(i, line)| (i + 1, line));
+
+    macro_rules! err {
+        ($line: expr, $column: expr, $kind: expr) => {
+            return Err(ParseError {
+                line: $line,
+                column: $column,
+                kind: $kind,
+            })
+        }
+    }
+
+    let mut name = None;
+    let mut size = None;
+    let mut max_steps = None;
+
+    let mut last_line_number = 0;
+    loop {
+        let (line_number, line) = match lines.next() {
+            Some(pair) => pair,
+            None => err!(last_line_number + 1, 1, ErrorKind::MissingSeparator),
+        };
+        last_line_number = line_number;
+
+        if line.trim().is_empty() {
+            break;
+        }
+
+        let colon_index = match line.find(':') {
+            Some(i) => i,
+            None => err!(line_number, 1, ErrorKind::MalformedHeaderLine),
+        };
+
+        let key = line[..colon_index].trim();
+        let value = line[colon_index + 1..].trim();
+        let value_column = line[..line.len() - line[colon_index + 1..].trim_start().len()]
+            .chars()
+            .count() + 1;
+
+        macro_rules! set_once {
+            ($field: ident, $parsed: expr) => {
+                if $field.is_some() {
+                    err!(line_number, 1, ErrorKind::DuplicateKey(key.to_string()));
+                }
+                $field = Some($parsed);
+            }
+        }
+
+        match key {
+            "name" => {
+                set_once!(name, value.to_string());
+            }
+            "size" => {
+                let parsed = match parse_size(value) {
+                    Some(parsed) => parsed,
+                    None => err!(line_number, value_column, ErrorKind::BadSize(value.to_string())),
+                };
+                set_once!(size, parsed);
+            }
+            "max_steps" => {
+                let parsed = match value.parse() {
+                    Ok(parsed) => parsed,
+                    Err(_) => {
+                        err!(line_number, value_column, ErrorKind::BadMaxSteps(value.to_string()))
+                    }
+                };
+                set_once!(max_steps, parsed);
+            }
+            _ => err!(line_number, 1, ErrorKind::UnknownKey(key.to_string())),
+        }
+    }
+
+    let size = match size {
+        Some(size) => size,
+        None => err!(1, 1, ErrorKind::MissingSize),
+    };
+
+    let mut cells = HashMap::new();
+    let mut blocks = Vec::new();
+    let mut start = None;
+    //where each teleporter number was first seen, and how many times
+    let mut teleporters: HashMap<u8, (usize, usize, u8)> = HashMap::new();
+
+    for y in 0..size.height {
+        let (line_number, line) = match lines.next() {
+            Some(pair) => pair,
+            None => {
+                err!(last_line_number + 1,
+                     1,
+                     ErrorKind::TooFewRows {
+                         expected: size.height,
+                         found: y,
+                     })
+            }
+        };
+        last_line_number = line_number;
+
+        let mut x = 0;
+        for c in line.chars() {
+            let column = x as usize + 1;
+
+            if x >= size.width {
+                err!(line_number,
+                     column,
+                     ErrorKind::WrongRowLength {
+                         expected: size.width,
+                         found: line.chars().count() as i32,
+                     });
+            }
+
+            match c {
+                '.' => {}
+                '#' => {
+                    cells.insert((x, y), Wall);
+                }
+                'G' => {
+                    cells.insert((x, y), Goal);
+                }
+                ',' => {
+                    cells.insert((x, y), Rough);
+                }
+                'O' => {
+                    blocks.push((x, y));
+                }
+                '~' => {
+                    cells.insert((x, y), Cracked);
+                }
+                '_' => {
+                    cells.insert((x, y), Hole);
+                }
+                '^' => {
+                    cells.insert((x, y), Arrow(Up));
+                }
+                '>' => {
+                    cells.insert((x, y), Arrow(Right));
+                }
+                'v' => {
+                    cells.insert((x, y), Arrow(Down));
+                }
+                '<' => {
+                    cells.insert((x, y), Arrow(Left));
+                }
+                'a'..='e' => {
+                    cells.insert((x, y), Key(c as u8 - b'a'));
+                }
+                'A'..='E' => {
+                    cells.insert((x, y), Door(c as u8 - b'A'));
+                }
+                '0'..='9' => {
+                    let pair = c as u8 - b'0';
+                    cells.insert((x, y), Teleporter(pair));
+                    teleporters.entry(pair).or_insert((line_number, column, 0)).2 += 1;
+                }
+                'S' => {
+                    if start.is_some() {
+                        err!(line_number, column, ErrorKind::MultipleStarts);
+                    }
+                    start = Some((x, y));
+                }
+                _ => err!(line_number, column, ErrorKind::UnexpectedChar(c)),
+            }
+
+            x += 1;
+        }
+
+        if x < size.width {
+            err!(line_number,
+                 x as usize + 1,
+                 ErrorKind::WrongRowLength {
+                     expected: size.width,
+                     found: x,
+                 });
+        }
+    }
+
+    for (line_number, line) in lines {
+        if !line.trim().is_empty() {
+            err!(line_number, 1, ErrorKind::TooManyRows);
+        }
+    }
+
+    let start = match start {
+        Some(start) => start,
+        None => err!(1, 1, ErrorKind::MissingStart),
+    };
+
+    let mut unpaired: Vec<_> = teleporters.into_iter()
+        .filter(|&(_, (_, _, count))| count != 2)
+        .map(|(pair, (line_number, column, _))| (line_number, column, pair))
+        .collect();
+    unpaired.sort();
+    if let Some(&(line_number, column, pair)) = unpaired.first() {
+        err!(line_number, column, ErrorKind::UnpairedTeleporter(pair));
+    }
+
+    Ok(Level {
+        name: name.unwrap_or_default(),
+        size: size,
+        start: start,
+        cells: cells,
+        blocks: blocks,
+        max_steps: max_steps.unwrap_or(DEFAULT_MAX_STEPS),
+    })
+}
+
+fn parse_size(value: &str) -> Option<Size> {
+    let mut parts = value.splitn(2, 'x');
+
+    let width: i32 = parts.next()?.trim().parse().ok()?;
+    let height: i32 = parts.next()?.trim().parse().ok()?;
+
+    let sides = 1..MAX_BOARD_SIDE + 1;
+    if sides.contains(&width) && sides.contains(&height) {
+        Some(Size::new(width, height))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use campaign;
+
+    const EXAMPLE: &str = "name: Around The Corner
+size: 8x4
+max_steps: 3
+
+.....#..
+....G...
+....#...
+S.......
+";
+
+    #[test]
+    fn parse_reads_this_example() {
+        let level = parse(EXAMPLE).unwrap();
+
+        assert_eq!(level.name, "Around The Corner");
+        assert_eq!(level.size, Size::new(8, 4));
+        assert_eq!(level.max_steps, 3);
+        assert_eq!(level.start, (0, 3));
+        assert_eq!(level.cells.len(), 3);
+        assert_eq!(level.cells.get(&(4, 1)), Some(&Goal));
+        assert_eq!(level.cells.get(&(4, 2)), Some(&Wall));
+    }
+
+    #[test]
+    fn serializing_then_parsing_round_trips_this_example() {
+        let level = parse(EXAMPLE).unwrap();
+
+        assert_eq!(level.to_string(), EXAMPLE);
+        assert_eq!(parse(&level.to_string()), Ok(level));
+    }
+
+    #[test]
+    fn arrows_rough_ground_blocks_and_cracked_ice_round_trip() {
+        let text = EXAMPLE.replace(".....#..", ".^>v<#,O").replace("....#...", "~_..#...");
+        let level = parse(&text).unwrap();
+
+        assert_eq!(level.cells.get(&(1, 0)), Some(&Arrow(Up)));
+        assert_eq!(level.cells.get(&(2, 0)), Some(&Arrow(Right)));
+        assert_eq!(level.cells.get(&(3, 0)), Some(&Arrow(Down)));
+        assert_eq!(level.cells.get(&(4, 0)), Some(&Arrow(Left)));
+        assert_eq!(level.cells.get(&(6, 0)), Some(&Rough));
+        assert_eq!(level.blocks, vec![(7, 0)]);
+        assert_eq!(level.cells.get(&(0, 2)), Some(&Cracked));
+        assert_eq!(level.cells.get(&(1, 2)), Some(&Hole));
+        assert_eq!(level.to_string(), text);
+    }
+
+    #[test]
+    fn teleporters_round_trip() {
+        let text = EXAMPLE.replace(".....#..", "1....#.1").replace("S.......", "S..0..0.");
+        let level = parse(&text).unwrap();
+
+        assert_eq!(level.cells.get(&(0, 0)), Some(&Teleporter(1)));
+        assert_eq!(level.cells.get(&(7, 0)), Some(&Teleporter(1)));
+        assert_eq!(level.cells.get(&(3, 3)), Some(&Teleporter(0)));
+        assert_eq!(level.to_string(), text);
+    }
+
+    #[test]
+    fn keys_and_doors_round_trip() {
+        let text = EXAMPLE.replace("....#...", "a.A.#.eE");
+        let level = parse(&text).unwrap();
+
+        assert_eq!(level.cells.get(&(0, 2)), Some(&Key(0)));
+        assert_eq!(level.cells.get(&(2, 2)), Some(&Door(0)));
+        assert_eq!(level.cells.get(&(6, 2)), Some(&Key(4)));
+        assert_eq!(level.cells.get(&(7, 2)), Some(&Door(4)));
+        assert_eq!(level.to_string(), text);
+    }
+
+    #[test]
+    fn every_kind_of_cell_round_trips_through_parse() {
+        let text = "size: 12x4
+max_steps: 4
+
+.#G,^>v<~_..
+0123456789..
+abcdeABCDE..
+9876543210SO
+";
+        let level = parse(text).unwrap();
+
+        assert_eq!(level.check(), Ok(()));
+        assert_eq!(level.to_string(), text);
+        assert_eq!(parse(&level.to_string()), Ok(level));
+    }
+
+    #[test]
+    fn check_rejects_what_would_not_round_trip() {
+        let level = parse(EXAMPLE).unwrap();
+        let start = level.start;
+
+        let mut goal_under_start = level.clone();
+        goal_under_start.cells.insert(start, Goal);
+        assert_eq!(goal_under_start.check(), Err(WriteError::Stacked(start)));
+
+        let mut block_on_wall = level.clone();
+        block_on_wall.blocks.push((4, 2));
+        assert_eq!(block_on_wall.check(), Err(WriteError::Stacked((4, 2))));
+
+        let mut big_teleporters = level.clone();
+        big_teleporters.cells.insert((0, 0), Teleporter(10));
+        big_teleporters.cells.insert((1, 0), Teleporter(10));
+        assert_eq!(big_teleporters.check(),
+                   Err(WriteError::NoCharacter((0, 0), Teleporter(10))));
+
+        let mut big_key = level.clone();
+        big_key.cells.insert((0, 0), Key(5));
+        assert_eq!(big_key.check(), Err(WriteError::NoCharacter((0, 0), Key(5))));
+
+        let mut big_door = level.clone();
+        big_door.cells.insert((0, 0), Door(5));
+        assert_eq!(big_door.check(), Err(WriteError::NoCharacter((0, 0), Door(5))));
+
+        let mut unpaired = level.clone();
+        unpaired.cells.insert((0, 0), Teleporter(2));
+        assert_eq!(unpaired.check(), Err(WriteError::UnpairedTeleporter(2)));
+    }
+
+    #[test]
+    fn every_campaign_level_round_trips() {
+        for world in campaign::WORLDS.iter() {
+            for text in world.levels.iter() {
+                let level = parse(text).unwrap();
+
+                assert_eq!(level.check(), Ok(()), "{}", level.name);
+                assert_eq!(parse(&level.to_string()), Ok(level));
+            }
+        }
+    }
+
+    #[test]
+    fn parse_reports_the_first_end_of_unpaired_teleporters() {
+        let text = EXAMPLE.replace("....G...", "....G.3.");
+
+        assert_eq!(parse(&text),
+                   Err(ParseError {
+                       line: 6,
+                       column: 7,
+                       kind: ErrorKind::UnpairedTeleporter(3),
+                   }));
+
+        let text = EXAMPLE.replace("....G...", "2...G.2.").replace("S.......", "S.2.....");
+
+        assert_eq!(parse(&text).map_err(|e| e.kind),
+                   Err(ErrorKind::UnpairedTeleporter(2)));
+    }
+
+    #[test]
+    fn parse_reports_the_position_of_unexpected_characters() {
+        let text = EXAMPLE.replace("....G...", "....G.?.");
+
+        assert_eq!(parse(&text),
+                   Err(ParseError {
+                       line: 6,
+                       column: 7,
+                       kind: ErrorKind::UnexpectedChar('?'),
+                   }));
+    }
+
+    #[test]
+    fn parse_reports_short_rows_at_their_end() {
+        let text = EXAMPLE.replace("....#...", "....#.");
+
+        assert_eq!(parse(&text),
+                   Err(ParseError {
+                       line: 7,
+                       column: 7,
+                       kind: ErrorKind::WrongRowLength {
+                           expected: 8,
+                           found: 6,
+                       },
+                   }));
+    }
+
+    #[test]
+    fn parse_reports_the_position_of_bad_sizes() {
+        let text = EXAMPLE.replace("size: 8x4", "size: 8 by 4");
+
+        assert_eq!(parse(&text),
+                   Err(ParseError {
+                       line: 2,
+                       column: 7,
+                       kind: ErrorKind::BadSize("8 by 4".to_string()),
+                   }));
+    }
+
+    #[test]
+    fn parse_requires_a_start() {
+        let text = EXAMPLE.replace("S", ".");
+
+        assert_eq!(parse(&text).map_err(|e| e.kind), Err(ErrorKind::MissingStart));
+    }
+}
@@ -0,0 +1,192 @@
+//! Picking which campaign level to play. Each world is a row of buttons.
+//!
+//! * The arrow keys, (or WASD,) move between levels.
+//! * `R` or `Enter` plays the selected level, if it is unlocked.
+//! * Pointing at a level selects it, and clicking it plays it.
+
+use common::*;
+use common::Motion::*;
+
+use std::collections::HashMap;
+
+use xs::Xs;
+
+use campaign;
+
+use super::{draw_button, game_update_and_render, goal_string, is_mouse_over,
+            new_state as new_title_state};
+
+const BUTTON_SIZE: i32 = 3;
+const BUTTON_SPACING: i32 = BUTTON_SIZE + 1;
+const LEFT: i32 = 1;
+const TOP: i32 = 2;
+
+pub fn new_state(size: Size, rng: Xs, session: Session, cursor: LevelId) -> State {
+    State {
+        size: size,
+        player_pos: (0, 0),
+        initial_player_pos: (0, 0),
+        cells: HashMap::new(),
+        blocks: Vec::new(),
+        rng: rng,
+        title_screen: false,
+        frame_count: 0,
+        motion: Stopped,
+        slide: Slide::default(),
+        max_steps: 4,
+        par: None,
+        editor: None,
+        history: History::default(),
+        hint: None,
+        hints_used: 0,
+        session: session,
+        daily: None,
+        campaign_level: None,
+        level_select: Some(LevelSelect { cursor: cursor }),
+    }
+}
+
+//returns true if quit requested
+pub fn update_and_render(platform: &Platform,
+                         state: &mut State,
+                         events: &mut Vec<Event>,
+                         dt: f32)
+                         -> bool {
+    let mut select = match state.level_select {
+        Some(select) => select,
+        None => return false,
+    };
+
+    for event in events {
+        match *event {
+            Event::Close |
+            Event::KeyPressed { key: KeyCode::Escape, ctrl: _, shift: _ } => return true,
+            Event::KeyPressed { key: KeyCode::W, ctrl: _, shift: _ } |
+            Event::KeyPressed { key: KeyCode::Up, ctrl: _, shift: _ } => {
+                select.cursor = move_cursor(select.cursor, Up);
+            }
+            Event::KeyPressed { key: KeyCode::D, ctrl: _, shift: _ } |
+            Event::KeyPressed { key: KeyCode::Right, ctrl: _, shift: _ } => {
+                select.cursor = move_cursor(select.cursor, Right);
+            }
+            Event::KeyPressed { key: KeyCode::S, ctrl: _, shift: _ } |
+            Event::KeyPressed { key: KeyCode::Down, ctrl: _, shift: _ } => {
+                select.cursor = move_cursor(select.cursor, Down);
+            }
+            Event::KeyPressed { key: KeyCode::A, ctrl: _, shift: _ } |
+            Event::KeyPressed { key: KeyCode::Left, ctrl: _, shift: _ } => {
+                select.cursor = move_cursor(select.cursor, Left);
+            }
+            Event::KeyPressed { key: KeyCode::R, ctrl: true, shift: _ } => {
+                *state = new_title_state((platform.size)(),
+                                         xs::new_seed(&mut state.rng),
+                                         state.session.clone());
+                return false;
+            }
+            Event::KeyPressed { key: KeyCode::R, ctrl: false, shift: _ } |
+            Event::KeyPressed { key: KeyCode::Enter, ctrl: _, shift: _ } => {
+                if !play(state, select.cursor) {
+                    continue;
+                }
+
+                return game_update_and_render(platform, state, &mut Vec::new(), dt);
+            }
+            Event::MouseMove { x: _, y: _ } => {
+                if let Some(id) = level_under_mouse(platform) {
+                    select.cursor = id;
+                }
+            }
+            Event::KeyPressed { key: KeyCode::MouseLeft, ctrl: _, shift: _ } => {
+                if let Some(id) = level_under_mouse(platform) {
+                    select.cursor = id;
+
+                    if play(state, id) {
+                        return game_update_and_render(platform, state, &mut Vec::new(), dt);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    state.level_select = Some(select);
+
+    draw(platform, state, select);
+
+    false
+}
+
+/// Starts the level `id`, if it is unlocked. Returns whether it did.
+fn play(state: &mut State, id: LevelId) -> bool {
+    if !campaign::is_unlocked(&state.session.progress, id) {
+        return false;
+    }
+
+    match campaign::new_state(state.rng, state.session.clone(), id) {
+        Some(level) => {
+            *state = level;
+            true
+        }
+        None => false,
+    }
+}
+
+fn button_xy(id: LevelId) -> (i32, i32) {
+    (LEFT + id.level as i32 * BUTTON_SPACING, TOP + id.world as i32 * BUTTON_SPACING)
+}
+
+fn level_under_mouse(platform: &Platform) -> Option<LevelId> {
+    campaign::ids().find(|&id| {
+        let (x, y) = button_xy(id);
+
+        is_mouse_over(platform, x, y, BUTTON_SIZE, BUTTON_SIZE)
+    })
+}
+
+fn move_cursor(cursor: LevelId, dir: Motion) -> LevelId {
+    let last_world = campaign::WORLDS.len().saturating_sub(1);
+
+    let world = match dir {
+        Up => cursor.world.saturating_sub(1),
+        Down => std::cmp::min(cursor.world + 1, last_world),
+        _ => cursor.world,
+    };
+
+    let last_level = campaign::WORLDS
+        .get(world)
+        .map(|w| w.levels.len().saturating_sub(1))
+        .unwrap_or(0);
+
+    let level = match dir {
+        Left => cursor.level.saturating_sub(1),
+        Right => cursor.level + 1,
+        _ => cursor.level,
+    };
+
+    LevelId {
+        world: world,
+        level: std::cmp::min(level, last_level),
+    }
+}
+
+fn draw(platform: &Platform, state: &State, select: LevelSelect) {
+    for id in campaign::ids() {
+        let label = if state.session.progress.completed.contains(&id) {
+            goal_string(state.frame_count)
+        } else if campaign::is_unlocked(&state.session.progress, id) {
+            "☐"
+        } else {
+            "☒"
+        };
+
+        let (x, y) = button_xy(id);
+
+        draw_button(platform,
+                    x,
+                    y,
+                    BUTTON_SIZE,
+                    BUTTON_SIZE,
+                    label,
+                    id == select.cursor);
+    }
+}
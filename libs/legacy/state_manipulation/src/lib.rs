@@ -1,5 +1,5 @@
 extern crate common;
-extern crate rand;
+extern crate xs;
 
 use common::*;
 use common::Cell::*;
@@ -7,7 +7,16 @@ use common::Motion::*;
 
 use std::collections::HashMap;
 
-use rand::{StdRng, SeedableRng, Rng};
+use xs::{Xs, Seed};
+
+pub mod campaign;
+mod daily;
+mod editor;
+mod history;
+pub mod level_format;
+mod level_select;
+pub mod save;
+pub mod solver;
 
 //NOTE(Ryan1729): debug_assertions only appears to work correctly when the
 //crate is not a dylib. Assuming you make this crate *not* a dylib on release,
@@ -19,24 +28,14 @@ use rand::{StdRng, SeedableRng, Rng};
     //println!("debug {}",
              //if cfg!(debug_assertions) { "on" } else { "off" });
 //
-    //let seed: &[_] = &[42];
-    //let rng: StdRng = SeedableRng::from_seed(seed);
-//
-    //next_level(size, rng, 4)
+    //next_level(size, xs::from_seed([42; 16]), 4, Session::default())
 //}
 //
 //#[cfg(not(debug_assertions))]
 #[no_mangle]
-pub fn new_state(size: Size) -> State {
+pub fn new_state(size: Size, seed: Seed, session: Session) -> State {
     //show the title screen
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map(|dur| dur.as_secs())
-        .unwrap_or(42);
-
-    println!("{}", timestamp);
-    let seed: &[_] = &[timestamp as usize];
-    let rng: StdRng = SeedableRng::from_seed(seed);
+    let rng = xs::from_seed(seed);
 
     let mut cells = HashMap::new();
 
@@ -56,88 +55,184 @@ pub fn new_state(size: Size) -> State {
     let player_pos = (5, 3);
 
     State {
+        size: size,
         player_pos: player_pos,
         initial_player_pos: player_pos,
         cells: cells,
+        blocks: Vec::new(),
         rng: rng,
         title_screen: true,
         frame_count: 0,
         motion: Stopped,
-        max_steps: 4,
+        slide: Slide::default(),
+        max_steps: FIRST_LEVEL_MAX_STEPS,
+        par: None,
+        editor: None,
+        history: History::default(),
+        hint: None,
+        hints_used: 0,
+        session: session,
+        daily: None,
+        campaign_level: None,
+        level_select: None,
     }
 }
 
 const START_POS: (i32, i32) = (7, 3);
 
+/// The `max_steps` of the first level after the title screen.
+const FIRST_LEVEL_MAX_STEPS: u8 = 4;
+
+/// Boards can be anywhere from 1x1 up to this many cells along each side.
+pub const MAX_BOARD_SIDE: i32 = 32;
+
+/// Random levels start small and grow with their difficulty, so that early on
+/// there is less open ice to get lost in.
+fn random_level_size(max_steps: u8) -> Size {
+    let side = (max_steps as i32 * 2).clamp(8, MAX_BOARD_SIDE);
+
+    Size::new(side, side)
+}
+
+/// A 1-based count of how far into the generated levels `state` is.
+pub fn level_number(state: &State) -> u32 {
+    state.max_steps.saturating_sub(FIRST_LEVEL_MAX_STEPS) as u32 + 1
+}
+
+/// How many moves have been made on the current level, counting the slide
+/// the player is in the middle of, if any.
+pub fn move_count(state: &State) -> u32 {
+    state.history.undo.len() as u32 + state.history.in_progress.is_some() as u32
+}
+
 #[no_mangle]
-//returns true if quit requested
-pub fn update_and_render(platform: &Platform, state: &mut State, events: &mut Vec<Event>) -> bool {
+//returns true if quit requested. `dt` is the seconds since the last update
+pub fn update_and_render(platform: &Platform,
+                         state: &mut State,
+                         events: &mut Vec<Event>,
+                         dt: f32)
+                         -> bool {
     state.frame_count = state.frame_count.overflowing_add(1).0;
 
     if state.title_screen {
         for event in events {
+            if let Event::KeyPressed { key: KeyCode::MouseLeft, ctrl: _, shift: _ } = *event {
+                let (x, y) = SOUND_BUTTON;
+
+                if is_mouse_over(platform, x, y, TITLE_BUTTON_SIZE, TITLE_BUTTON_SIZE) {
+                    state.session.settings.sound = !state.session.settings.sound;
+                    continue;
+                }
+            }
+
+            let event = &title_button_event(platform, *event);
+
             cross_mode_event_handling(platform, state, event);
 
             match *event {
                 Event::Close |
                 Event::KeyPressed { key: KeyCode::Escape, ctrl: _, shift: _ } => return true,
+                Event::KeyPressed { key: KeyCode::Enter, ctrl: _, shift: _ } => {
+                    *state = editor::new_state((platform.size)(), state.rng, state.session.clone());
+                    return editor::update_and_render(platform, state, &mut Vec::new(), dt);
+                }
+                Event::KeyPressed { key: KeyCode::Tab, ctrl: _, shift: _ } => {
+                    *state = daily::new_state(state.session.clone());
+                    return game_update_and_render(platform, state, &mut Vec::new(), dt);
+                }
+                Event::KeyPressed { key: KeyCode::X, ctrl: _, shift: _ } => {
+                    let cursor = campaign::first_incomplete(&state.session.progress);
+                    *state = level_select::new_state((platform.size)(),
+                                                     state.rng,
+                                                     state.session.clone(),
+                                                     cursor);
+                    return level_select::update_and_render(platform, state, &mut Vec::new(), dt);
+                }
                 _ => (),
             }
         }
 
         if state.player_pos == START_POS {
-            *state = next_level((platform.size)(), state.rng, state.max_steps);
+            *state = next_level(random_level_size(state.max_steps),
+                                state.rng,
+                                state.max_steps,
+                                state.session.clone());
         } else {
-            move_player((platform.size)(), state);
+            move_player(state.size, state, dt);
         }
 
         print_tuple(platform, START_POS, goal_string(state.frame_count));
 
         draw(platform, state);
 
-        draw_button(platform,
-                    5,
-                    9,
-                    3,
-                    3,
-                    "↑",
-                    (platform.key_pressed)(KeyCode::Up));
-        draw_button(platform,
-                    2,
-                    12,
-                    3,
-                    3,
-                    "←",
-                    (platform.key_pressed)(KeyCode::Left));
-        draw_button(platform,
-                    5,
-                    12,
-                    3,
-                    3,
-                    "↓",
-                    (platform.key_pressed)(KeyCode::Down));
-        draw_button(platform,
-                    8,
-                    12,
-                    3,
-                    3,
-                    "→",
-                    (platform.key_pressed)(KeyCode::Right));
+        for &((x, y), label, key) in TITLE_BUTTONS.iter() {
+            let held = (platform.key_pressed)(key) ||
+                       ((platform.key_pressed)(KeyCode::MouseLeft) &&
+                        is_mouse_over(platform, x, y, TITLE_BUTTON_SIZE, TITLE_BUTTON_SIZE));
+
+            draw_button(platform,
+                        x,
+                        y,
+                        TITLE_BUTTON_SIZE,
+                        TITLE_BUTTON_SIZE,
+                        label,
+                        held);
+        }
 
+        let (x, y) = SOUND_BUTTON;
         draw_button(platform,
-                    12,
-                    12,
-                    3,
-                    3,
-                    "R",
-                    (platform.key_pressed)(KeyCode::R));
+                    x,
+                    y,
+                    TITLE_BUTTON_SIZE,
+                    TITLE_BUTTON_SIZE,
+                    "M",
+                    !state.session.settings.sound);
 
         false
+    } else if state.editor.is_some() {
+        editor::update_and_render(platform, state, events, dt)
+    } else if state.level_select.is_some() {
+        level_select::update_and_render(platform, state, events, dt)
     } else {
-        game_update_and_render(platform, state, events)
+        game_update_and_render(platform, state, events, dt)
     }
 }
 
+const TITLE_BUTTON_SIZE: i32 = 3;
+/// Where the title screen's buttons go, and the keys they stand for.
+const TITLE_BUTTONS: [((i32, i32), &str, KeyCode); 5] = [((5, 9), "↑", KeyCode::Up),
+                                                       ((2, 12), "←", KeyCode::Left),
+                                                       ((5, 12), "↓", KeyCode::Down),
+                                                       ((8, 12), "→", KeyCode::Right),
+                                                       ((12, 12), "R", KeyCode::R)];
+
+/// Clicking this mutes the sound, or unmutes it. It stays pressed in while
+/// the sound is muted.
+const SOUND_BUTTON: (i32, i32) = (12, 9);
+
+/// A click on one of the title screen's buttons counts as pressing its key.
+fn title_button_event(platform: &Platform, event: Event) -> Event {
+    if let Event::KeyPressed { key: KeyCode::MouseLeft, ctrl, shift } = event {
+        for &((x, y), _, key) in TITLE_BUTTONS.iter() {
+            if is_mouse_over(platform, x, y, TITLE_BUTTON_SIZE, TITLE_BUTTON_SIZE) {
+                return Event::KeyPressed {
+                    key: key,
+                    ctrl: ctrl,
+                    shift: shift,
+                };
+            }
+        }
+    }
+
+    event
+}
+
+fn is_mouse_over(platform: &Platform, x: i32, y: i32, w: i32, h: i32) -> bool {
+    let mouse = (platform.mouse_position)();
+
+    mouse.x >= x && mouse.y >= y && mouse.x < x + w && mouse.y < y + h
+}
+
 fn draw_button(platform: &Platform, x: i32, y: i32, w: i32, h: i32, label: &'static str, pressed: bool) {
 
     if pressed {
@@ -149,25 +244,60 @@ fn draw_button(platform: &Platform, x: i32, y: i32, w: i32, h: i32, label: &'sta
     (platform.print_xy)(x + 1, y + 1, label);
 }
 
+/// While playing a level:
+///
+/// * The arrow keys, (or WASD,) slide the player.
+/// * `R` starts the level over.
+/// * `X` undoes a slide, and `Tab` redoes it.
+/// * `Enter` shows a hint: the next move of a shortest solution.
 pub fn game_update_and_render(platform: &Platform,
                               state: &mut State,
-                              events: &mut Vec<Event>)
+                              events: &mut Vec<Event>,
+                              dt: f32)
                               -> bool {
     for event in events {
         cross_mode_event_handling(platform, state, event);
+        history_event_handling(state, event);
 
         match *event {
             Event::Close |
             Event::KeyPressed { key: KeyCode::Escape, ctrl: _, shift: _ } => return true,
+            Event::KeyPressed { key: KeyCode::Enter, ctrl: _, shift: _ } => {
+                show_hint(state.size, state);
+            }
             _ => (),
         }
     }
 
-    move_player((platform.size)(), state);
+    move_player(state.size, state, dt);
 
-    if let Some(&Goal) = state.cells.get(&state.player_pos) {
-        state.max_steps += 1;
-        *state = next_level((platform.size)(), state.rng, state.max_steps);
+    if reached_goal(state) {
+        if let Some(id) = state.campaign_level {
+            //the winning slide can still be going, so it needs counting too
+            history::finish(state);
+
+            let moves = state.history.undo.len() as u32;
+            let progress = &mut state.session.progress;
+
+            progress.completed.insert(id);
+            let best = progress.best.entry(id).or_insert(moves);
+            *best = std::cmp::min(*best, moves);
+
+            let cursor = campaign::next(id).unwrap_or(id);
+            *state = level_select::new_state((platform.size)(),
+                                             state.rng,
+                                             state.session.clone(),
+                                             cursor);
+            return false;
+        } else if state.daily.is_some() {
+            daily::reached_goal(platform, state);
+        } else {
+            state.max_steps += 1;
+            *state = next_level(random_level_size(state.max_steps),
+                                state.rng,
+                                state.max_steps,
+                                state.session.clone());
+        }
     }
 
     draw(platform, state);
@@ -175,71 +305,344 @@ pub fn game_update_and_render(platform: &Platform,
     false
 }
 
-fn move_player(size: Size, state: &mut State) {
+const BUMP_FRAMES: u8 = 8;
+/// How far, as a fraction of a cell, the player pushes into a wall they hit.
+const BUMP_DEPTH: f32 = 0.125;
+
+/// Easing out starts this many cells before the player stops.
+const EASE_OUT_CELLS: f32 = 2.0;
+/// The slowest easing out makes the player, as a fraction of their speed, so
+/// they do still arrive.
+const MIN_EASE_OUT: f32 = 0.25;
+
+fn move_player(size: Size, state: &mut State, dt: f32) {
+    let settings = state.session.settings.slide;
+
     match state.motion {
-        Stopped => {}
+        Stopped => {
+            state.slide.bump = match state.slide.bump {
+                Some((dir, frames)) if frames > 1 => Some((dir, frames - 1)),
+                _ => None,
+            };
+        }
         dir => {
             let target = add(state.player_pos, dir_to_tuple(dir));
-            if can_go(size, &state.cells, target) {
-                state.player_pos = target;
-            } else {
-                state.motion = Stopped;
+            let push = match step(size, &state.cells, &state.blocks, target, dir) {
+                Step::Move => None,
+                Step::Push(index, destination) => Some((index, destination)),
+                Step::Blocked => {
+                    state.motion = Stopped;
+                    state.slide = Slide {
+                        bump: Some((dir, BUMP_FRAMES)),
+                        ..Slide::default()
+                    };
+                    history::finish(state);
+                    return;
+                }
+            };
+
+            let slide = &mut state.slide;
+
+            slide.speed = match settings.acceleration {
+                Some(acceleration) => {
+                    f32::min(slide.speed + acceleration * dt,
+                             settings.speed)
+                }
+                None => settings.speed,
+            };
+
+            let mut speed = slide.speed;
+            if settings.ease_out {
+                let remaining = match solver::slide(size,
+                                                    &state.cells,
+                                                    &state.blocks,
+                                                    state.player_pos,
+                                                    dir) {
+                    solver::Path { stop: Some(_), passed, .. } => passed.len() as f32,
+                    //going round in circles, so never stopping
+                    solver::Path { stop: None, .. } => f32::INFINITY,
+                } - slide.progress;
+                speed *= (remaining / EASE_OUT_CELLS).clamp(MIN_EASE_OUT, 1.0);
+            }
+
+            //never more than a cell a frame, so no cell is slid past unchecked
+            slide.progress += f32::min(speed * dt, 1.0);
+
+            if slide.progress >= 1.0 {
+                if state.cells.get(&state.player_pos) == Some(&Cracked) {
+                    state.cells.insert(state.player_pos, Hole);
+                }
+
+                if state.cells.get(&target) == Some(&Hole) {
+                    //falling in fails the level, so it starts over
+                    history::reset(state);
+                    return;
+                }
+
+                collect_key(&mut state.cells, target);
+
+                let (pos, dir) = match push {
+                    Some((index, destination)) => {
+                        //the player stops where the block was
+                        state.blocks[index] = destination;
+                        (target, Stopped)
+                    }
+                    None => arrive(&state.cells, &state.blocks, target, dir),
+                };
+                state.player_pos = pos;
+                state.motion = dir;
+
+                if dir == Stopped {
+                    //no bump, since nothing was hit
+                    state.slide = Slide::default();
+                    history::finish(state);
+                    return;
+                }
+
+                let next = add(pos, dir_to_tuple(dir));
+                slide.progress = if step(size, &state.cells, &state.blocks, next, dir) != Step::Blocked {
+                    slide.progress - 1.0
+                } else {
+                    //rather than drawing the player partway into the wall
+                    0.0
+                };
             }
         }
     }
 }
 
+/// How far from `player_pos` the player should be drawn, in cells, so they
+/// move smoothly from one cell to the next.
+pub fn player_draw_offset(state: &State) -> (f32, f32) {
+    let (dir, distance) = match (state.motion, state.slide.bump) {
+        (Stopped, Some((dir, frames))) => {
+            //into the wall and back out again
+            let t = frames as f32 / BUMP_FRAMES as f32;
+            (dir, BUMP_DEPTH * (1.0 - (2.0 * t - 1.0).abs()))
+        }
+        (Stopped, None) => return (0.0, 0.0),
+        (dir, _) => (dir, state.slide.progress),
+    };
+
+    let (x, y) = dir_to_tuple(dir);
+
+    (x as f32 * distance, y as f32 * distance)
+}
+
 fn cross_mode_event_handling(platform: &Platform, state: &mut State, event: &Event) {
     match *event {
         Event::KeyPressed { key: KeyCode::W, ctrl: _, shift: _ } |
         Event::KeyPressed { key: KeyCode::Up, ctrl: _, shift: _ } => {
-            if state.motion == Stopped {
-                state.motion = Up;
-            }
+            start_moving(state, Up);
         }
         Event::KeyPressed { key: KeyCode::D, ctrl: _, shift: _ } |
         Event::KeyPressed { key: KeyCode::Right, ctrl: _, shift: _ } => {
-            if state.motion == Stopped {
-                state.motion = Right;
-            }
+            start_moving(state, Right);
         }
         Event::KeyPressed { key: KeyCode::S, ctrl: _, shift: _ } |
         Event::KeyPressed { key: KeyCode::Down, ctrl: _, shift: _ } => {
-            if state.motion == Stopped {
-                state.motion = Down;
-            }
+            start_moving(state, Down);
         }
         Event::KeyPressed { key: KeyCode::A, ctrl: _, shift: _ } |
         Event::KeyPressed { key: KeyCode::Left, ctrl: _, shift: _ } => {
-            if state.motion == Stopped {
-                state.motion = Left;
-            }
+            start_moving(state, Left);
         }
         Event::KeyPressed { key: KeyCode::R, ctrl: false, shift: _ } => {
             println!("reset level");
-            state.player_pos = state.initial_player_pos;
+            history::reset(state);
         }
         Event::KeyPressed { key: KeyCode::R, ctrl: true, shift: _ } => {
             println!("reset");
-            *state = new_state((platform.size)());
+            *state = new_state((platform.size)(), xs::new_seed(&mut state.rng), state.session.clone());
+        }
+        Event::KeyPressed { key: KeyCode::MouseLeft, ctrl: _, shift: _ } => {
+            let mouse = (platform.mouse_position)();
+
+            if let Some(dir) = direction_towards(state.player_pos, (mouse.x, mouse.y)) {
+                start_moving(state, dir);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Undo and redo, for when a level is being played. The title screen uses
+/// these keys for other things.
+fn history_event_handling(state: &mut State, event: &Event) {
+    match *event {
+        Event::KeyPressed { key: KeyCode::X, ctrl: _, shift: _ } => {
+            history::undo(state);
+        }
+        Event::KeyPressed { key: KeyCode::Tab, ctrl: _, shift: _ } => {
+            history::redo(state);
         }
         _ => (),
     }
 }
 
-fn can_go(size: Size, cells: &Cells, (x, y): (i32, i32)) -> bool {
+/// Which way to go from `from` to get to `to`, if they are in a line.
+fn direction_towards(from: (i32, i32), to: (i32, i32)) -> Option<Motion> {
+    match sub(to, from) {
+        (0, 0) => None,
+        (0, y) => Some(if y < 0 { Up } else { Down }),
+        (x, 0) => Some(if x < 0 { Left } else { Right }),
+        _ => None,
+    }
+}
+
+fn start_moving(state: &mut State, dir: Motion) {
+    //the level is over, (this only lasts for the daily puzzle)
+    if reached_goal(state) {
+        return;
+    }
+
+    if state.motion == Stopped {
+        state.motion = dir;
+        state.slide = Slide::default();
+        state.hint = None;
+        history::start(state, dir);
+    }
+}
+
+fn show_hint(size: Size, state: &mut State) {
+    if state.motion != Stopped {
+        return;
+    }
+
+    let hint = match solver::solve(size, &state.cells, &state.blocks, state.player_pos) {
+        Some(moves) => {
+            match moves.first() {
+                Some(&dir) => Hint::Move(dir),
+                //already on the goal
+                None => return,
+            }
+        }
+        None => Hint::Stuck,
+    };
+
+    state.hint = Some(hint);
+    state.hints_used += 1;
+}
+
+/// Whether a player sliding in `dir` can move onto `(x, y)`.
+fn can_go(size: Size, cells: &Cells, (x, y): (i32, i32), dir: Motion) -> bool {
     if x >= 0 && y >= 0 && x < size.width && y < size.height {
 
         match cells.get(&(x, y)) {
             None => true,
             Some(&Goal) => true,
-            Some(&Wall) => false,
+            Some(&Rough) |
+            Some(&Teleporter(_)) |
+            Some(&Cracked) |
+            Some(&Hole) => true,
+            Some(&Key(_)) => true,
+            Some(&Wall) |
+            Some(&Door(_)) => false,
+            Some(&Arrow(arrow)) => arrow != opposite(dir),
         }
     } else {
         false
     }
 }
 
+/// What happens when a player sliding in `dir` tries to move onto `target`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Step {
+    Move,
+    Blocked,
+    /// The block at this index in `blocks` gets pushed, and slides to here.
+    Push(usize, (i32, i32)),
+}
+
+fn step(size: Size, cells: &Cells, blocks: &[(i32, i32)], target: (i32, i32), dir: Motion) -> Step {
+    match blocks.iter().position(|&block| block == target) {
+        Some(index) => {
+            match block_destination(size, cells, blocks, target, dir) {
+                Some(destination) => Step::Push(index, destination),
+                None => Step::Blocked,
+            }
+        }
+        None if can_go(size, cells, target, dir) => Step::Move,
+        None => Step::Blocked,
+    }
+}
+
+/// Where the block at `pos` ends up when pushed in `dir`, or `None` if it
+/// cannot move at all. Blocks slide straight over everything except walls,
+/// other blocks and the board edge, which stop them.
+fn block_destination(size: Size,
+                     cells: &Cells,
+                     blocks: &[(i32, i32)],
+                     pos: (i32, i32),
+                     dir: Motion)
+                     -> Option<(i32, i32)> {
+    let mut current = pos;
+    loop {
+        let (x, y) = add(current, dir_to_tuple(dir));
+
+        if x < 0 || y < 0 || x >= size.width || y >= size.height || blocks.contains(&(x, y)) {
+            break;
+        }
+
+        match cells.get(&(x, y)) {
+            Some(&Wall) | Some(&Door(_)) => break,
+            _ => {}
+        }
+
+        current = (x, y);
+    }
+
+    if current == pos { None } else { Some(current) }
+}
+
+/// Where a player sliding in `dir` ends up after moving onto `pos`, and which
+/// way they go on in from there. `Stopped` if they stop there.
+fn arrive(cells: &Cells,
+          blocks: &[(i32, i32)],
+          pos: (i32, i32),
+          dir: Motion)
+          -> ((i32, i32), Motion) {
+    match cells.get(&pos) {
+        Some(&Rough) | Some(&Hole) => (pos, Stopped),
+        Some(&Arrow(Stopped)) => (pos, dir),
+        Some(&Arrow(arrow)) => (pos, arrow),
+        Some(&Teleporter(pair)) => {
+            match partner(cells, pos, pair) {
+                //a block sitting on the other end keeps the player here
+                Some(other) if !blocks.contains(&other) => (other, dir),
+                _ => (pos, dir),
+            }
+        }
+        _ => (pos, dir),
+    }
+}
+
+/// Picks up the key at `pos`, if there is one, opening its doors.
+fn collect_key(cells: &mut Cells, pos: (i32, i32)) {
+    if let Some(&Key(colour)) = cells.get(&pos) {
+        cells.remove(&pos);
+        cells.retain(|_, cell| *cell != Door(colour));
+    }
+}
+
+/// Whether the goals count yet, which they do once every key is picked up.
+fn goal_is_active(cells: &Cells) -> bool {
+    !cells.values().any(|&cell| matches!(cell, Key(_)))
+}
+
+fn reached_goal(state: &State) -> bool {
+    state.cells.get(&state.player_pos) == Some(&Goal) && goal_is_active(&state.cells)
+}
+
+/// The other teleporter with the same number as the one at `pos`, if any.
+fn partner(cells: &Cells, pos: (i32, i32), pair: u8) -> Option<(i32, i32)> {
+    //the smallest, so a level with extras still behaves the same every time
+    cells.iter()
+        .filter(|&(&other, &cell)| other != pos && cell == Teleporter(pair))
+        .map(|(&other, _)| other)
+        .min()
+}
+
 fn goal_string(frame_count: u32) -> &'static str {
     match frame_count & 31 {
         1 => "\u{E010}",
@@ -277,6 +680,15 @@ fn goal_string(frame_count: u32) -> &'static str {
     }
 }
 
+/// Unlike the goal, which winds up and then back down, teleporters keep
+/// spinning the same way.
+fn teleporter_string(frame_count: u32) -> &'static str {
+    const FRAMES: [&str; 9] = ["\u{E010}", "\u{E011}", "\u{E012}", "\u{E013}", "\u{E014}",
+                                "\u{E015}", "\u{E016}", "\u{E017}", "\u{E018}"];
+
+    FRAMES[(frame_count / 2 % FRAMES.len() as u32) as usize]
+}
+
 fn print_tuple(platform: &Platform, (x, y): (i32, i32), text: &'static str) {
     if x >= 0 && y >= 0 {
         (platform.print_xy)(x, y, text);
@@ -296,17 +708,66 @@ macro_rules! with_layer {
     }
 }
 
+macro_rules! with_foreground {
+    ($platform:expr, $colour:expr, $code:block) => {
+        {
+            let current = ($platform.get_foreground)();
+            ($platform.set_foreground)($colour);
+
+            $code
+
+            ($platform.set_foreground)(current);
+        }
+    }
+}
+
 fn draw(platform: &Platform, state: &State) {
+    let goal_active = goal_is_active(&state.cells);
     for (&coords, &cell) in state.cells.iter() {
-        print_cell(platform, coords, cell, state.frame_count);
+        print_cell(platform, coords, cell, goal_active, state.frame_count);
     }
 
     print_tuple(platform, state.initial_player_pos, "☐");
 
+    for &block in state.blocks.iter() {
+        print_tuple(platform, block, BLOCK_STR);
+    }
+
     with_layer!(platform, 1, {
         print_tuple(platform, state.player_pos, "@");
-    })
+    });
+
+    if let Some(hint) = state.hint {
+        with_layer!(platform, 2, {
+            with_foreground!(platform, HINT_COLOUR, {
+                print_tuple(platform, state.player_pos, hint_string(hint));
+            })
+        })
+    }
+}
 
+const BLOCK_STR: &str = "■";
+
+const HINT_COLOUR: Color = Color { red: 0xFF, green: 0xB9, blue: 0x37, alpha: 0xFF };
+
+/// Each key is drawn in the same colour as the doors it opens.
+const KEY_COLOURS: [Color; 5] = [
+    Color { red: 0x33, green: 0x52, blue: 0xE1, alpha: 0xFF },
+    Color { red: 0x30, green: 0xB0, blue: 0x6E, alpha: 0xFF },
+    Color { red: 0xDE, green: 0x49, blue: 0x49, alpha: 0xFF },
+    Color { red: 0xB5, green: 0x5C, blue: 0xD6, alpha: 0xFF },
+    Color { red: 0x5A, green: 0x7D, blue: 0x8B, alpha: 0xFF },
+];
+
+fn hint_string(hint: Hint) -> &'static str {
+    match hint {
+        Hint::Move(Up) => "↑",
+        Hint::Move(Right) => "→",
+        Hint::Move(Down) => "↓",
+        Hint::Move(Left) => "←",
+        //suggest a reset
+        Hint::Move(Stopped) | Hint::Stuck => "R",
+    }
 }
 
 fn draw_unpressed_button_rect(platform: &Platform, x: i32, y: i32, w: i32, h: i32) {
@@ -353,200 +814,196 @@ fn draw_rect_with(platform: &Platform, x: i32, y: i32, w: i32, h: i32, edges: [&
     (platform.print_xy)(right, bottom, edges[7]);
 }
 
-fn print_cell(platform: &Platform, coords: (i32, i32), cell: Cell, frame_count: u32) {
+const INACTIVE_GOAL_STR: &str = "\u{E019}";
+
+fn print_cell(platform: &Platform,
+              coords: (i32, i32),
+              cell: Cell,
+              goal_active: bool,
+              frame_count: u32) {
     match cell {
-        Goal => print_tuple(platform, coords, goal_string(frame_count)),
+        Goal if goal_active => print_tuple(platform, coords, goal_string(frame_count)),
+        Goal => print_tuple(platform, coords, INACTIVE_GOAL_STR),
+        Teleporter(_) => print_tuple(platform, coords, teleporter_string(frame_count)),
+        Key(colour) | Door(colour) => {
+            let colour = KEY_COLOURS[colour as usize % KEY_COLOURS.len()];
+            with_foreground!(platform, colour, {
+                print_tuple(platform, coords, cell.to_static_str());
+            })
+        }
         _ => print_tuple(platform, coords, cell.to_static_str()),
     }
     // with_layer!(platform, CELL_LAYER, {
     // })
 }
 
-fn next_level(size: Size, mut rng: StdRng, max_steps: u8) -> State {
-    let mut cells = HashMap::new();
+//if no board gives a long enough solution within this many tries, we settle
+//for the hardest one we saw, rather than possibly never finishing. A try on
+//the biggest boards takes a few milliseconds, so this also keeps the pause
+//before a new level short
+const MAX_GENERATION_ATTEMPTS: u32 = 64;
 
-    for y in 0..size.height {
-        for x in 0..size.width {
-            if rng.next_f32() > 0.9 {
-                cells.insert((x, y), Wall);
-            }
-        }
-    }
+/// Random boards seldom have a longer shortest solution than this, even at
+/// `MAX_BOARD_SIDE`, so levels past it all aim for this many moves, rather
+/// than searching through many boards for one that may never turn up.
+const MAX_GENERATED_STEPS: u8 = 16;
 
-    let mut player_pos = gen_coord(size, &mut rng);
+/// A generated board, with where the player starts, how many moves it takes
+/// to get to each cell, and the most moves it takes to get to plain ice.
+type Board = (Cells, (i32, i32), HashMap<(i32, i32), u32>, u32);
 
-    if let Some(_) = cells.get(&player_pos) {
-        let first_player_pos = player_pos;
-        while let Some(_) = cells.get(&player_pos) {
-            player_pos = next_coord(size, player_pos);
+fn next_level(size: Size, mut rng: Xs, max_steps: u8, session: Session) -> State {
+    let target = max_steps.clamp(1, MAX_GENERATED_STEPS) as u32;
 
-            if player_pos == first_player_pos {
-                cells.remove(&player_pos);
-            }
-        }
-    }
+    let mut hardest: Option<Board> = None;
+    let mut attempts = 0;
 
-    let mut counts: HashMap<(i32, i32), u32> = HashMap::new();
+    let (mut cells, player_pos, counts, moves) = loop {
+        attempts += 1;
 
-    for dirs in DirsIter::new(max_steps) {
-        let mut current_pos = player_pos;
+        let (cells, player_pos) = gen_board(size, &mut rng);
 
-        for &dir in dirs.iter() {
-            loop {
-                let target = add(current_pos, dir_to_tuple(dir));
-                if can_go(size, &cells, target) {
-                    current_pos = target;
-                    increment_count(&mut counts, current_pos)
-                } else {
-                    break;
-                }
-            }
+        let counts = solver::move_counts(size, &cells, &[], player_pos);
+
+        //the goal goes on plain ice, since covering up an arrow with it could
+        //change how many moves it takes to get there
+        let plain_counts = || {
+            counts.iter().filter(|&(coord, _)| !cells.contains_key(coord)).map(|(_, &v)| v)
+        };
+
+        if plain_counts().any(|v| v == target) {
+            break (cells, player_pos, counts, target);
         }
-    }
 
-    let mut non_zero_minimum_count = std::u32::MAX;
+        let most_moves = plain_counts().max().unwrap_or(0);
 
-    for &v in counts.values() {
-        if v != 0 && v < non_zero_minimum_count {
-            non_zero_minimum_count = v;
+        //the first board always counts, so there is something to settle for
+        //even if no board lets the player go anywhere
+        let is_hardest = match hardest {
+            Some((_, _, _, moves)) => most_moves > moves,
+            None => true,
+        };
+        if is_hardest {
+            hardest = Some((cells, player_pos, counts, most_moves));
         }
-    }
+
+        if attempts >= MAX_GENERATION_ATTEMPTS {
+            if let Some(board) = hardest.take() {
+                break board;
+            }
+        }
+    };
 
     //we do the sort so that the rng seed determines the puzzle,
     //not the hash ordering
     let mut goal_locations: Vec<(i32, i32)> = counts.iter()
-        .filter(|&(_, &v)| v == non_zero_minimum_count)
+        .filter(|&(coord, &v)| v == moves && !cells.contains_key(coord))
         .map(|(&coord, _)| coord)
         .collect();
 
-    goal_locations.sort_by_key(|&(coord, _)| coord);
+    goal_locations.sort();
 
     let mut len = goal_locations.len();
-    if len > 0 {
-        loop {
-            let possible_goal = goal_locations.swap_remove(rng.gen_range(0, len));
-
-            len = goal_locations.len();
-            if not_on_edge(size, possible_goal) || len == 0 {
-                cells.insert(possible_goal, Goal);
-                break;
-            }
+    loop {
+        let possible_goal = goal_locations.swap_remove(xs::range(&mut rng, 0..len as u32) as usize);
+
+        len = goal_locations.len();
+        if not_on_edge(size, possible_goal) || len == 0 {
+            cells.insert(possible_goal, Goal);
+            break;
         }
-    } else {
-        cells.insert(player_pos, Goal);
     }
 
     State {
+        size: size,
         player_pos: player_pos,
         initial_player_pos: player_pos,
         cells: cells,
+        blocks: Vec::new(),
         rng: rng,
         title_screen: false,
         frame_count: 0,
         motion: Stopped,
+        slide: Slide::default(),
         max_steps: max_steps,
+        par: Some(moves),
+        editor: None,
+        history: History::default(),
+        hint: None,
+        hints_used: 0,
+        session: session,
+        daily: None,
+        campaign_level: None,
+        level_select: None,
     }
 }
 
-fn not_on_edge(size: Size, (x, y): (i32, i32)) -> bool {
-    x != 0 && y != 0 && x != size.width - 1 && y != size.height - 1
-}
+const MAX_GENERATED_CRACKED: u32 = 3;
 
-struct DirsIter {
-    index: u16,
-    started: bool,
-    max: u8,
-    max_index: u16,
-}
+fn gen_board(size: Size, rng: &mut Xs) -> (Cells, (i32, i32)) {
+    let mut cells = HashMap::new();
 
-impl DirsIter {
-    //if max_+index is not of the form (2 ^ 2n) - 1
-    //certain directions will be favoured over others
-    fn new(max: u8) -> Self {
-        let max_index = if max < 8 {
-            (1 << (2 * max)) - 1
-        } else {
-            std::u16::MAX
-        };
+    for y in 0..size.height {
+        for x in 0..size.width {
+            match xs::range(rng, 0..40) {
+                0..=3 => {
+                    cells.insert((x, y), Wall);
+                }
+                5..=6 => {
+                    cells.insert((x, y), Rough);
+                }
+                4 => {
+                    let dir = solver::DIRECTIONS[xs::range(rng, 0..4) as usize];
+                    cells.insert((x, y), Arrow(dir));
+                }
+                _ => {}
+            }
+        }
+    }
 
-        DirsIter {
-            index: std::u16::MAX,
-            started: false,
-            max: max,
-            max_index: max_index,
+    //at most one pair of teleporters, since more than that gets hard to follow
+    if xs::range(rng, 0..2) == 0 {
+        let ends = (gen_coord(size, rng), gen_coord(size, rng));
+        if ends.0 != ends.1 {
+            cells.insert(ends.0, Teleporter(0));
+            cells.insert(ends.1, Teleporter(0));
         }
     }
-}
 
-impl Iterator for DirsIter {
-    type Item = Vec<Motion>;
+    //only a few cracked cells, since the solver has to try breaking each
+    //combination of them
+    for _ in 0..xs::range(rng, 0..MAX_GENERATED_CRACKED + 1) {
+        let pos = gen_coord(size, rng);
+        cells.entry(pos).or_insert(Cracked);
+    }
 
-    fn next(&mut self) -> Option<Vec<Motion>> {
-        if self.started && (self.index == std::u16::MAX || self.index >= self.max_index) {
-            None
-        } else {
-            self.started = true;
-            self.index = self.index.overflowing_add(1).0;
+    let mut player_pos = gen_coord(size, rng);
 
-            let mut result = Vec::new();
+    if let Some(_) = cells.get(&player_pos) {
+        let first_player_pos = player_pos;
+        while let Some(_) = cells.get(&player_pos) {
+            player_pos = next_coord(size, player_pos);
 
-            for &mask in TwoBits::all_values().iter() {
-                if result.len() as u8 >= self.max {
-                    break;
-                } else {
-                    match extract_dir(mask, self.index) {
-                        Stopped => {}
-                        dir => result.push(dir),
-                    }
-                }
+            if player_pos == first_player_pos {
+                cells.remove(&player_pos);
             }
-
-            Some(result)
         }
     }
-}
-
-#[derive(Copy, Clone)]
-enum TwoBits {
-    First,
-    Second,
-    Third,
-    Fourth,
-    Fifth,
-    Sixth,
-    Seventh,
-    Eighth,
-}
-use TwoBits::*;
 
-pub trait AllValues {
-    fn all_values() -> Vec<Self> where Self: std::marker::Sized;
+    (cells, player_pos)
 }
 
-impl AllValues for TwoBits {
-    fn all_values() -> Vec<TwoBits> {
-        vec![First, Second, Third, Fourth, Fifth, Sixth, Seventh, Eighth]
-    }
+fn not_on_edge(size: Size, (x, y): (i32, i32)) -> bool {
+    x != 0 && y != 0 && x != size.width - 1 && y != size.height - 1
 }
 
-fn extract_dir(mask: TwoBits, index: u16) -> Motion {
-    let bits = match mask {
-        First => index & 0b11,
-        Second => (index & 0b1100) >> 2,
-        Third => (index & 0b110000) >> 4,
-        Fourth => (index & 0b11000000) >> 6,
-        Fifth => (index & 0b1100000000) >> 8,
-        Sixth => (index & 0b110000000000) >> 10,
-        Seventh => (index & 0b11000000000000) >> 12,
-        Eighth => (index & 0b1100000000000000) >> 14,
-    };
-
-    match bits {
-        0b00 => Up,
-        0b01 => Right,
-        0b10 => Down,
-        0b11 => Left,
-        _ => Stopped,
+fn opposite(dir: Motion) -> Motion {
+    match dir {
+        Up => Down,
+        Right => Left,
+        Down => Up,
+        Left => Right,
+        Stopped => Stopped,
     }
 }
 
@@ -562,8 +1019,8 @@ fn dir_to_tuple(dir: Motion) -> (i32, i32) {
 
 
 
-fn gen_coord(size: Size, rng: &mut StdRng) -> (i32, i32) {
-    (rng.gen_range::<i32>(0, size.width), rng.gen_range::<i32>(0, size.height))
+fn gen_coord(size: Size, rng: &mut Xs) -> (i32, i32) {
+    (xs::range(rng, 0..size.width as u32) as i32, xs::range(rng, 0..size.height as u32) as i32)
 }
 
 fn next_coord(size: Size, (x, y): (i32, i32)) -> (i32, i32) {
@@ -591,7 +1048,335 @@ fn sub<T: Sub<Output = T>>((x1, y1): (T, T), (x2, y2): (T, T)) -> (T, T) {
     (x1 - x2, y1 - y2)
 }
 
-fn increment_count(counts: &mut HashMap<(i32, i32), u32>, key: (i32, i32)) {
-    let count = counts.entry(key).or_insert(0);
-    *count = count.saturating_add(1);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direction_towards_only_gives_a_direction_along_a_row_or_column() {
+        assert_eq!(direction_towards((3, 3), (3, 0)), Some(Up));
+        assert_eq!(direction_towards((3, 3), (9, 3)), Some(Right));
+        assert_eq!(direction_towards((3, 3), (3, 4)), Some(Down));
+        assert_eq!(direction_towards((3, 3), (-1, 3)), Some(Left));
+        assert_eq!(direction_towards((3, 3), (3, 3)), None);
+        assert_eq!(direction_towards((3, 3), (4, 4)), None);
+    }
+
+    #[test]
+    fn next_level_generates_levels_whose_shortest_solution_is_max_steps_long() {
+        let size = Size::new(16, 16);
+
+        for seed in 0..8 {
+            for max_steps in 1..12 {
+                let rng = xs::from_seed([seed; 16]);
+
+                let state = next_level(size, rng, max_steps, Session::default());
+
+                let solution = solver::solve(size, &state.cells, &state.blocks, state.player_pos)
+                    .expect("generated level should be solvable");
+
+                assert_eq!(solution.len(), max_steps as usize, "seed {}", seed);
+            }
+        }
+    }
+
+    #[test]
+    fn next_level_aims_no_higher_than_boards_can_reliably_go() {
+        let max_steps = MAX_GENERATED_STEPS + 20;
+        let rng = xs::from_seed([2; 16]);
+
+        let state = next_level(random_level_size(max_steps), rng, max_steps, Session::default());
+
+        assert_eq!(state.par, Some(MAX_GENERATED_STEPS as u32));
+        assert_eq!(state.max_steps, max_steps);
+    }
+
+    #[test]
+    fn next_level_gives_up_even_when_no_board_lets_the_player_move() {
+        let rng = xs::from_seed([1; 16]);
+
+        let state = next_level(Size::new(1, 1), rng, 4, Session::default());
+
+        assert_eq!(state.par, Some(0));
+        assert_eq!(state.cells.get(&state.player_pos), Some(&Goal));
+    }
+
+    #[test]
+    fn generated_levels_fit_inside_their_own_size() {
+        for max_steps in 1..20 {
+            let size = random_level_size(max_steps);
+            let rng = xs::from_seed([max_steps; 16]);
+
+            let state = next_level(size, rng, max_steps, Session::default());
+
+            assert_eq!(state.size, size);
+            assert!(size.width >= 8 && size.width <= MAX_BOARD_SIDE, "{:?}", size);
+
+            let in_bounds = |(x, y): (i32, i32)| {
+                x >= 0 && y >= 0 && x < size.width && y < size.height
+            };
+            assert!(in_bounds(state.player_pos), "{:?}", state.player_pos);
+            for &pos in state.cells.keys() {
+                assert!(in_bounds(pos), "{:?} in {:?}", pos, size);
+            }
+        }
+    }
+
+    const DT: f32 = 1.0 / 60.0;
+
+    /// For running whole frames, without drawing them anywhere.
+    pub fn headless_platform() -> Platform {
+        Platform {
+            print_xy: |_, _, _| {},
+            clear: |_| {},
+            size: || Size::new(16, 16),
+            pick: |_, _| ' ',
+            mouse_position: Point::default,
+            clicks: || 0,
+            key_pressed: |_| false,
+            set_colors: |_, _| {},
+            get_colors: || (Color::default(), Color::default()),
+            set_foreground: |_| {},
+            get_foreground: Color::default,
+            set_background: |_| {},
+            get_background: Color::default,
+            set_layer: |_| {},
+            get_layer: || 0,
+            log: |_| {},
+        }
+    }
+
+    /// Presses `key` while playing, then runs frames until the player stops.
+    pub fn press(platform: &Platform, state: &mut State, key: KeyCode) {
+        let mut events = vec![Event::KeyPressed {
+                                  key: key,
+                                  ctrl: false,
+                                  shift: false,
+                              }];
+
+        for _ in 0..600 {
+            game_update_and_render(platform, state, &mut events, DT);
+            events.clear();
+
+            if state.motion == Stopped {
+                return;
+            }
+        }
+
+        panic!("the player never stopped");
+    }
+
+    #[test]
+    fn completing_a_campaign_level_counts_the_winning_slide() {
+        // .S....G. so one slide right, which passes over the goal
+        let id = LevelId {
+            world: 0,
+            level: 0,
+        };
+        let mut state = campaign::new_state(xs::from_seed([1; 16]), Session::default(), id)
+            .unwrap();
+
+        press(&headless_platform(), &mut state, KeyCode::Right);
+
+        assert_eq!(state.campaign_level, None);
+        assert!(state.session.progress.completed.contains(&id));
+        assert_eq!(state.session.progress.best.get(&id), Some(&1));
+    }
+
+    fn empty_level(size: Size, slide: SlideSettings) -> State {
+        let mut session = Session::default();
+        session.settings.slide = slide;
+
+        let mut state = next_level(size, xs::from_seed([1; 16]), 1, session);
+        state.cells.clear();
+        state.player_pos = (0, 0);
+
+        state
+    }
+
+    fn frames_to_slide(state: &mut State, dir: Motion) -> u32 {
+        start_moving(state, dir);
+
+        let mut frames = 0;
+        while state.motion != Stopped {
+            move_player(state.size, state, DT);
+            frames += 1;
+        }
+        frames
+    }
+
+    #[test]
+    fn at_a_cell_a_frame_sliding_is_as_fast_as_it_can_be() {
+        let mut state = empty_level(Size::new(8, 8),
+                                    SlideSettings {
+                                        speed: 1.0 / DT,
+                                        acceleration: None,
+                                        ease_out: false,
+                                    });
+
+        //seven cells, then noticing the edge
+        assert_eq!(frames_to_slide(&mut state, Right), 8);
+        assert_eq!(state.player_pos, (7, 0));
+        assert_eq!(state.slide.bump, Some((Right, BUMP_FRAMES)));
+    }
+
+    #[test]
+    fn move_count_includes_the_slide_in_progress() {
+        let mut state = empty_level(Size::new(8, 8), SlideSettings::default());
+
+        start_moving(&mut state, Right);
+        move_player(state.size, &mut state, DT);
+
+        assert_ne!(state.motion, Stopped);
+        assert_eq!(move_count(&state), 1);
+
+        while state.motion != Stopped {
+            move_player(state.size, &mut state, DT);
+        }
+        assert_eq!(move_count(&state), 1);
+
+        frames_to_slide(&mut state, Down);
+
+        assert_eq!(move_count(&state), 2);
+    }
+
+    #[test]
+    fn pushing_a_block_stops_the_player_where_it_was() {
+        let mut state = empty_level(Size::new(8, 8), SlideSettings::default());
+        state.blocks = vec![(4, 0)];
+
+        frames_to_slide(&mut state, Right);
+
+        assert_eq!(state.player_pos, (4, 0));
+        assert_eq!(state.blocks, vec![(7, 0)]);
+        assert_eq!(state.slide.bump, None);
+
+        //and with nowhere for the block to go, it is like a wall
+        frames_to_slide(&mut state, Right);
+
+        assert_eq!(state.player_pos, (6, 0));
+        assert_eq!(state.blocks, vec![(7, 0)]);
+    }
+
+    #[test]
+    fn falling_into_a_hole_starts_the_level_over_and_undo_mends_cracked_ice() {
+        let mut state = empty_level(Size::new(8, 8), SlideSettings::default());
+        state.initial_player_pos = (0, 0);
+        state.cells.insert((3, 0), Cracked);
+
+        frames_to_slide(&mut state, Right);
+        frames_to_slide(&mut state, Down);
+
+        assert_eq!(state.player_pos, (7, 7));
+        assert_eq!(state.cells.get(&(3, 0)), Some(&Hole));
+
+        history::undo(&mut state);
+        history::undo(&mut state);
+        assert_eq!(state.cells.get(&(3, 0)), Some(&Cracked));
+
+        history::redo(&mut state);
+        frames_to_slide(&mut state, Left);
+
+        assert_eq!(state.player_pos, (0, 0));
+        assert_eq!(state.cells.get(&(3, 0)), Some(&Cracked));
+        assert!(state.history.undo.is_empty());
+    }
+
+    #[test]
+    fn keys_open_their_doors_and_the_goal_waits_for_every_key() {
+        // S . a . . . . .
+        // . . . . . . . .
+        // . . A . . . . .
+        // . . G . b . . .
+        let mut state = empty_level(Size::new(8, 8), SlideSettings::default());
+        state.cells.insert((2, 0), Key(0));
+        state.cells.insert((2, 2), Door(0));
+        state.cells.insert((2, 3), Goal);
+        state.cells.insert((4, 3), Key(1));
+
+        frames_to_slide(&mut state, Right);
+
+        assert_eq!(state.player_pos, (7, 0));
+        assert_eq!(state.cells.get(&(2, 0)), None);
+        assert_eq!(state.cells.get(&(2, 2)), None);
+
+        state.player_pos = (2, 0);
+        frames_to_slide(&mut state, Down);
+
+        //the other key is still out there
+        assert_eq!(state.player_pos, (2, 7));
+        assert!(!goal_is_active(&state.cells));
+
+        collect_key(&mut state.cells, (4, 3));
+        assert!(goal_is_active(&state.cells));
+    }
+
+    #[test]
+    fn easing_and_acceleration_take_longer_but_stop_in_the_same_place() {
+        let mut state = empty_level(Size::new(8, 8), SlideSettings::default());
+
+        let mut previous_offset = 0.0;
+        start_moving(&mut state, Down);
+        while state.motion != Stopped {
+            move_player(state.size, &mut state, DT);
+
+            //the drawn position never goes backwards
+            let (_, offset) = player_draw_offset(&state);
+            let offset = state.player_pos.1 as f32 + offset;
+            assert!(offset >= previous_offset, "{} < {}", offset, previous_offset);
+            previous_offset = offset;
+        }
+
+        assert_eq!(state.player_pos, (0, 7));
+
+        state.player_pos = (0, 0);
+        assert!(frames_to_slide(&mut state, Down) > 8);
+        assert_eq!(state.player_pos, (0, 7));
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_sequence_of_levels() {
+        let size = Size::new(16, 16);
+
+        let play_through = |seed| {
+            let mut state = new_state(size, seed, Session::default());
+            let mut levels = Vec::new();
+            for max_steps in 1..6 {
+                state = next_level(size, state.rng, max_steps, Session::default());
+                levels.push((state.player_pos, state.cells.clone()));
+            }
+            levels
+        };
+
+        assert_eq!(play_through([7; 16]), play_through([7; 16]));
+        assert_ne!(play_through([7; 16]), play_through([8; 16]));
+    }
+
+    #[test]
+    fn show_hint_points_along_a_shortest_solution() {
+        let size = Size::new(16, 16);
+        let rng = xs::from_seed([3; 16]);
+        let mut state = next_level(size, rng, 5, Session::default());
+
+        show_hint(size, &mut state);
+
+        let solution = solver::solve(size, &state.cells, &state.blocks, state.player_pos).unwrap();
+        assert_eq!(state.hint, Some(Hint::Move(solution[0])));
+        assert_eq!(state.hints_used, 1);
+
+        start_moving(&mut state, solution[0]);
+        assert_eq!(state.hint, None);
+    }
+
+    #[test]
+    fn show_hint_says_when_the_goal_cannot_be_reached() {
+        let size = Size::new(16, 16);
+        let rng = xs::from_seed([3; 16]);
+        let mut state = next_level(size, rng, 5, Session::default());
+        state.cells.retain(|_, cell| *cell != Goal);
+
+        show_hint(size, &mut state);
+
+        assert_eq!(state.hint, Some(Hint::Stuck));
+    }
 }
@@ -0,0 +1,185 @@
+//! Turning what should last between runs, (campaign progress and settings,)
+//! into text and back. For example:
+//!
+//! ```text
+//! completed 1-1
+//! completed 1-2
+//! best 1-1 1
+//! best 1-2 3
+//! sound on
+//! speed 24
+//! acceleration 96
+//! ease-out on
+//! ```
+//!
+//! `speed` and `acceleration` are in tiles per second, and tiles per second
+//! per second. `acceleration none` starts each slide at full speed.
+//!
+//! Levels are written as `<world>-<level>`, both starting at 1, as they are
+//! shown in game.
+
+use common::*;
+
+use std::fmt::Write;
+
+pub fn to_string(session: &Session) -> String {
+    let mut output = String::new();
+
+    for &id in session.progress.completed.iter() {
+        let _ = writeln!(output, "completed {}", id_to_string(id));
+    }
+
+    for (&id, &moves) in session.progress.best.iter() {
+        let _ = writeln!(output, "best {} {}", id_to_string(id), moves);
+    }
+
+    let _ = writeln!(output,
+                     "sound {}",
+                     if session.settings.sound { "on" } else { "off" });
+
+    let slide = session.settings.slide;
+
+    let _ = writeln!(output, "speed {}", slide.speed);
+
+    match slide.acceleration {
+        Some(acceleration) => {
+            let _ = writeln!(output, "acceleration {}", acceleration);
+        }
+        None => {
+            let _ = writeln!(output, "acceleration none");
+        }
+    }
+
+    let _ = writeln!(output, "ease-out {}", if slide.ease_out { "on" } else { "off" });
+
+    output
+}
+
+/// Lines that cannot be understood are skipped, so that a damaged save, or one
+/// from a different version, loses as little as possible.
+pub fn load(text: &str, session: &mut Session) {
+    for line in text.lines() {
+        let mut words = line.split_whitespace();
+
+        match (words.next(), words.next(), words.next()) {
+            (Some("completed"), Some(id), None) => {
+                if let Some(id) = parse_id(id) {
+                    session.progress.completed.insert(id);
+                }
+            }
+            (Some("best"), Some(id), Some(moves)) => {
+                if let (Some(id), Ok(moves)) = (parse_id(id), moves.parse()) {
+                    session.progress.best.insert(id, moves);
+                }
+            }
+            (Some("sound"), Some("on"), None) => {
+                session.settings.sound = true;
+            }
+            (Some("sound"), Some("off"), None) => {
+                session.settings.sound = false;
+            }
+            (Some("speed"), Some(speed), None) => {
+                if let Some(speed) = parse_rate(speed) {
+                    session.settings.slide.speed = speed;
+                }
+            }
+            (Some("acceleration"), Some("none"), None) => {
+                session.settings.slide.acceleration = None;
+            }
+            (Some("acceleration"), Some(acceleration), None) => {
+                if let Some(acceleration) = parse_rate(acceleration) {
+                    session.settings.slide.acceleration = Some(acceleration);
+                }
+            }
+            (Some("ease-out"), Some("on"), None) => {
+                session.settings.slide.ease_out = true;
+            }
+            (Some("ease-out"), Some("off"), None) => {
+                session.settings.slide.ease_out = false;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Speeds and accelerations that are not above zero would leave the player
+/// stuck partway through a slide, so they are not accepted.
+fn parse_rate(text: &str) -> Option<f32> {
+    let rate: f32 = text.parse().ok()?;
+
+    if rate.is_finite() && rate > 0.0 {
+        Some(rate)
+    } else {
+        None
+    }
+}
+
+fn id_to_string(id: LevelId) -> String {
+    format!("{}-{}", id.world + 1, id.level + 1)
+}
+
+fn parse_id(text: &str) -> Option<LevelId> {
+    let mut parts = text.splitn(2, '-');
+
+    let world: usize = parts.next()?.parse().ok()?;
+    let level: usize = parts.next()?.parse().ok()?;
+
+    Some(LevelId {
+        world: world.checked_sub(1)?,
+        level: level.checked_sub(1)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(world: usize, level: usize) -> LevelId {
+        LevelId {
+            world: world,
+            level: level,
+        }
+    }
+
+    #[test]
+    fn loading_what_was_saved_gives_back_the_same_progress_and_settings() {
+        let mut session = Session::default();
+        session.progress.completed.insert(id(0, 0));
+        session.progress.completed.insert(id(1, 3));
+        session.progress.best.insert(id(0, 0), 2);
+        session.progress.best.insert(id(1, 3), 9);
+        session.settings.sound = false;
+        session.settings.slide = SlideSettings {
+            speed: 12.5,
+            acceleration: None,
+            ease_out: false,
+        };
+
+        let mut loaded = Session::default();
+        load(&to_string(&session), &mut loaded);
+
+        assert_eq!(loaded, session);
+    }
+
+    #[test]
+    fn load_skips_lines_it_does_not_understand() {
+        let mut session = Session::default();
+
+        load("completed 1-2\ncompleted 0-1\nbest 1-2 lots\nvolume 11\n\ncompleted 2-1\n",
+             &mut session);
+
+        assert_eq!(session.progress.completed.iter().cloned().collect::<Vec<_>>(),
+                   vec![id(0, 1), id(1, 0)]);
+        assert!(session.progress.best.is_empty());
+    }
+
+    #[test]
+    fn load_skips_slide_settings_the_player_could_not_move_with() {
+        let mut session = Session::default();
+
+        load("speed 0\nspeed -3\nspeed inf\nacceleration NaN\nacceleration 0\n",
+             &mut session);
+
+        assert_eq!(session.settings.slide, SlideSettings::default());
+    }
+}
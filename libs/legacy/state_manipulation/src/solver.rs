@@ -0,0 +1,538 @@
+//! An exact solver for levels, that does a breadth-first search over the
+//! places a slide can stop at, along with where the blocks are then. Since
+//! each edge of the search is a single move, the first time we pass over a
+//! position is via a shortest sequence of moves, whatever the board size is.
+
+use common::*;
+use common::Cell::*;
+use common::Motion::*;
+
+use std::collections::{HashMap, VecDeque};
+use std::collections::hash_map::Entry;
+
+use super::{add, arrive, collect_key, dir_to_tuple, goal_is_active, step, Step};
+
+pub const DIRECTIONS: [Motion; 4] = [Up, Right, Down, Left];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Path {
+    /// Every position passed over, in order. Empty if the player cannot move
+    /// in that direction at all.
+    pub passed: Vec<(i32, i32)>,
+    /// Where the slide stops, or `None` if arrows send the player round in a
+    /// loop forever.
+    pub stop: Option<(i32, i32)>,
+    /// Where the blocks are after the slide.
+    pub blocks: Vec<(i32, i32)>,
+    /// The `Cracked` cells the slide broke, in the order it broke them.
+    pub broken: Vec<(i32, i32)>,
+    /// Where the keys the slide picked up were, in the order it picked them up.
+    pub collected: Vec<(i32, i32)>,
+    /// The first `Goal` passed over once it was active, if any.
+    pub reached: Option<(i32, i32)>,
+    /// Whether the slide ends by falling into a `Hole`, at `stop`.
+    pub fell: bool,
+}
+
+/// Returns where sliding from `start` in `dir` goes, following any arrows,
+/// pushing any block in the way, breaking any cracked ice slid off of, and
+/// picking up any keys, (which can open doors further along.)
+pub fn slide(size: Size,
+             cells: &Cells,
+             blocks: &[(i32, i32)],
+             start: (i32, i32),
+             dir: Motion)
+             -> Path {
+    let mut path = Path {
+        passed: Vec::new(),
+        stop: Some(start),
+        blocks: blocks.to_vec(),
+        broken: Vec::new(),
+        collected: Vec::new(),
+        reached: None,
+        fell: false,
+    };
+
+    if dir == Stopped {
+        return path;
+    }
+
+    //a slide that has gone further than there are ways to be in a position,
+    //(a position and a direction,) must be repeating itself
+    let longest = 4 * size.width as usize * size.height as usize;
+
+    //only copied if the slide changes the board
+    let mut changed: Option<Cells> = None;
+
+    let mut current_pos = start;
+    let mut dir = dir;
+    loop {
+        let target = add(current_pos, dir_to_tuple(dir));
+        let push = match step(size, changed.as_ref().unwrap_or(cells), &path.blocks, target, dir) {
+            Step::Move => None,
+            Step::Blocked => {
+                path.stop = Some(current_pos);
+                return path;
+            }
+            Step::Push(index, destination) => Some((index, destination)),
+        };
+
+        if path.passed.len() >= longest {
+            path.stop = None;
+            return path;
+        }
+
+        if changed.as_ref().unwrap_or(cells).get(&current_pos) == Some(&Cracked) {
+            changed.get_or_insert_with(|| cells.clone()).insert(current_pos, Hole);
+            path.broken.push(current_pos);
+        }
+
+        path.passed.push(target);
+
+        match changed.as_ref().unwrap_or(cells).get(&target) {
+            Some(&Hole) => {
+                path.stop = Some(target);
+                path.fell = true;
+                return path;
+            }
+            Some(&Key(_)) => {
+                collect_key(changed.get_or_insert_with(|| cells.clone()), target);
+                path.collected.push(target);
+            }
+            _ => {}
+        }
+
+        let current_cells = changed.as_ref().unwrap_or(cells);
+
+        if path.reached.is_none() && current_cells.get(&target) == Some(&Goal) &&
+           goal_is_active(current_cells) {
+            path.reached = Some(target);
+        }
+
+        if let Some((index, destination)) = push {
+            //the player stops where the block was
+            path.blocks[index] = destination;
+            path.stop = Some(target);
+            return path;
+        }
+
+        let (pos, next_dir) = arrive(current_cells, &path.blocks, target, dir);
+        if pos != target {
+            path.passed.push(pos);
+        }
+        current_pos = pos;
+        dir = next_dir;
+
+        if dir == Stopped {
+            path.stop = Some(current_pos);
+            return path;
+        }
+    }
+}
+
+/// Returns a shortest sequence of moves that passes over an active `Goal`
+/// when starting at `start`, or `None` if no sequence of moves does.
+pub fn solve(size: Size,
+             cells: &Cells,
+             blocks: &[(i32, i32)],
+             start: (i32, i32))
+             -> Option<Vec<Motion>> {
+    let search = Search::new(size, cells, blocks, start);
+
+    let mut goals: Vec<((i32, i32), u32)> = cells.iter()
+        .filter(|&(_, &cell)| matches!(cell, Goal))
+        .filter_map(|(&coord, _)| search.counts.get(&coord).map(|&count| (coord, count)))
+        .collect();
+
+    //sorting here keeps the chosen solution independent of the hash ordering
+    goals.sort_by_key(|&(coord, count)| (count, coord));
+
+    goals.first().map(|&(coord, _)| search.path_to(coord))
+}
+
+/// Returns the fewest moves needed to pass over each position that can be
+/// reached from `start`. `start` itself is included, with a count of 0.
+/// Passing over a `Goal` only counts once it is active.
+pub fn move_counts(size: Size,
+                   cells: &Cells,
+                   blocks: &[(i32, i32)],
+                   start: (i32, i32))
+                   -> HashMap<(i32, i32), u32> {
+    Search::new(size, cells, blocks, start).counts
+}
+
+/// Where the player has stopped, where the blocks are, which cracked ice has
+/// broken, and where the keys that have been picked up were, with each list
+/// sorted so the same arrangement is always the same `Node`.
+type Node = ((i32, i32), Vec<(i32, i32)>, Vec<(i32, i32)>, Vec<(i32, i32)>);
+
+struct Search {
+    start: Node,
+    counts: HashMap<(i32, i32), u32>,
+    /// The node and direction of the move that first passed over each
+    /// position, except the start position.
+    passed_via: HashMap<(i32, i32), (Node, Motion)>,
+    /// The node and direction of the move that first reached each node,
+    /// except `start`.
+    via: HashMap<Node, (Node, Motion)>,
+}
+
+impl Search {
+    fn new(size: Size, cells: &Cells, blocks: &[(i32, i32)], start: (i32, i32)) -> Self {
+        let mut blocks = blocks.to_vec();
+        blocks.sort();
+        let start = (start, blocks, Vec::new(), Vec::new());
+
+        let mut counts = HashMap::new();
+        let mut passed_via = HashMap::new();
+        let mut depths = HashMap::new();
+        let mut via = HashMap::new();
+
+        counts.insert(start.0, 0);
+        depths.insert(start.clone(), 0);
+
+        let mut queue = VecDeque::new();
+        queue.push_back(start.clone());
+
+        while let Some(node) = queue.pop_front() {
+            let next_count = depths[&node] + 1;
+
+            let mut node_cells;
+            let node_cells = if node.2.is_empty() && node.3.is_empty() {
+                cells
+            } else {
+                node_cells = cells.clone();
+                for &pos in node.2.iter() {
+                    node_cells.insert(pos, Hole);
+                }
+                for &pos in node.3.iter() {
+                    collect_key(&mut node_cells, pos);
+                }
+                &node_cells
+            };
+
+            for &dir in DIRECTIONS.iter() {
+                let path = slide(size, node_cells, &node.1, node.0, dir);
+
+                for &pos in path.passed.iter() {
+                    //an inactive goal is no different from ice
+                    if cells.get(&pos) == Some(&Goal) && path.reached != Some(pos) {
+                        continue;
+                    }
+
+                    if let Entry::Vacant(entry) = counts.entry(pos) {
+                        entry.insert(next_count);
+                        passed_via.insert(pos, (node.clone(), dir));
+                    }
+                }
+
+                if path.fell {
+                    continue;
+                }
+
+                if let Some(stop) = path.stop {
+                    let mut blocks = path.blocks;
+                    blocks.sort();
+                    let mut broken = node.2.clone();
+                    broken.extend(path.broken);
+                    broken.sort();
+                    let mut collected = node.3.clone();
+                    collected.extend(path.collected);
+                    collected.sort();
+                    let next = (stop, blocks, broken, collected);
+
+                    if !depths.contains_key(&next) {
+                        depths.insert(next.clone(), next_count);
+                        via.insert(next.clone(), (node.clone(), dir));
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        Search {
+            start: start,
+            counts: counts,
+            passed_via: passed_via,
+            via: via,
+        }
+    }
+
+    fn path_to(&self, target: (i32, i32)) -> Vec<Motion> {
+        let mut moves = Vec::new();
+
+        let mut current = match self.passed_via.get(&target) {
+            Some(&(ref node, dir)) => {
+                moves.push(dir);
+                node
+            }
+            //the start position
+            None => return moves,
+        };
+
+        while *current != self.start {
+            let &(ref node, dir) = &self.via[current];
+            moves.push(dir);
+            current = node;
+        }
+
+        moves.reverse();
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: Size = Size { width: 8, height: 8 };
+
+    fn cells_from(walls: &[(i32, i32)], goal: (i32, i32)) -> Cells {
+        let mut cells: Cells = walls.iter().map(|&coord| (coord, Wall)).collect();
+        cells.insert(goal, Goal);
+        cells
+    }
+
+    #[test]
+    fn slide_stops_before_walls_and_the_board_edge() {
+        let cells = cells_from(&[(5, 0)], (7, 7));
+
+        assert_eq!(slide(SIZE, &cells, &[], (1, 0), Right).passed, vec![(2, 0), (3, 0), (4, 0)]);
+        assert_eq!(slide(SIZE, &cells, &[], (1, 0), Up),
+                   Path {
+                       passed: vec![],
+                       stop: Some((1, 0)),
+                       blocks: vec![],
+                       broken: vec![],
+                       collected: vec![],
+                       reached: None,
+                       fell: false,
+                   });
+        assert_eq!(slide(SIZE, &cells, &[], (1, 5), Down).stop, Some((1, 7)));
+    }
+
+    #[test]
+    fn arrows_redirect_slides_onto_them_and_block_slides_against_them() {
+        let mut cells = cells_from(&[], (7, 7));
+        cells.insert((3, 0), Arrow(Down));
+
+        assert_eq!(slide(SIZE, &cells, &[], (0, 0), Right).passed,
+                   vec![(1, 0), (2, 0), (3, 0), (3, 1), (3, 2), (3, 3), (3, 4), (3, 5), (3, 6),
+                        (3, 7)]);
+        assert_eq!(slide(SIZE, &cells, &[], (3, 5), Up).stop, Some((3, 1)));
+    }
+
+    #[test]
+    fn rough_ground_stops_a_slide_on_it() {
+        let mut cells = cells_from(&[], (7, 7));
+        cells.insert((4, 0), Rough);
+
+        assert_eq!(slide(SIZE, &cells, &[], (0, 0), Right).stop, Some((4, 0)));
+        assert_eq!(slide(SIZE, &cells, &[], (4, 0), Right).stop, Some((7, 0)));
+    }
+
+    #[test]
+    fn pushing_a_block_sends_it_sliding_and_stops_the_player_where_it_was() {
+        let cells = cells_from(&[(6, 0)], (7, 7));
+
+        assert_eq!(slide(SIZE, &cells, &[(1, 0), (2, 0)], (0, 0), Right),
+                   Path {
+                       passed: vec![],
+                       stop: Some((0, 0)),
+                       blocks: vec![(1, 0), (2, 0)],
+                       broken: vec![],
+                       collected: vec![],
+                       reached: None,
+                       fell: false,
+                   });
+        assert_eq!(slide(SIZE, &cells, &[(3, 0)], (0, 0), Right),
+                   Path {
+                       passed: vec![(1, 0), (2, 0), (3, 0)],
+                       stop: Some((3, 0)),
+                       blocks: vec![(5, 0)],
+                       broken: vec![],
+                       collected: vec![],
+                       reached: None,
+                       fell: false,
+                   });
+    }
+
+    #[test]
+    fn solve_pushes_blocks_to_stop_where_nothing_else_would() {
+        // Without the block, the only stops are the corners, from which the
+        // goal cannot be passed over.
+        //
+        // S . . . . . . .
+        // . . . . . . . .
+        // . . . . . . . .
+        // . . . . . . . .
+        // B . . . G . . .
+        let cells = cells_from(&[], (4, 4));
+
+        assert_eq!(solve(SIZE, &cells, &[], (0, 0)), None);
+        assert_eq!(solve(SIZE, &cells, &[(0, 4)], (0, 0)), Some(vec![Down, Right]));
+    }
+
+    #[test]
+    fn teleporters_send_the_player_on_from_their_partner() {
+        // . . T . . . . .
+        // . . . . . . . .
+        // . . . . . . . .
+        // S . . . . T . #
+        let mut cells = cells_from(&[(7, 3)], (7, 7));
+        cells.insert((2, 0), Teleporter(1));
+        cells.insert((5, 3), Teleporter(1));
+
+        assert_eq!(slide(SIZE, &cells, &[], (0, 3), Right).passed,
+                   vec![(1, 3), (2, 3), (3, 3), (4, 3), (5, 3), (2, 0), (3, 0), (4, 0), (5, 0),
+                        (6, 0), (7, 0)]);
+        assert_eq!(slide(SIZE, &cells, &[], (2, 7), Up).stop, Some((5, 0)));
+
+        //a block on the far end stops the teleporter working
+        assert_eq!(slide(SIZE, &cells, &[(2, 0)], (0, 3), Right).stop, Some((6, 3)));
+    }
+
+    #[test]
+    fn cracked_ice_breaks_once_slid_off_and_holes_end_slides_in_a_fall() {
+        let mut cells = cells_from(&[], (7, 7));
+        cells.insert((3, 0), Cracked);
+
+        let path = slide(SIZE, &cells, &[], (0, 0), Right);
+        assert_eq!((path.stop, path.broken, path.fell), (Some((7, 0)), vec![(3, 0)], false));
+
+        cells.insert((3, 0), Hole);
+
+        let path = slide(SIZE, &cells, &[], (0, 0), Right);
+        assert_eq!((path.stop, path.broken, path.fell), (Some((3, 0)), vec![], true));
+    }
+
+    #[test]
+    fn solve_does_not_cross_cracked_ice_a_second_time() {
+        // The only way out of the start crosses the cracked ice, and the only
+        // way to the goal after that crosses it again.
+        //
+        // . . . R . . . .
+        // . . . . . . . .
+        // # . . . . . . .
+        // S . . C . . . .
+        // # . . . . . . .
+        // . . . . . . . .
+        // . . . G . . . .
+        // . . . . . . . .
+        let mut cells = cells_from(&[(0, 2), (0, 4)], (3, 6));
+        cells.insert((3, 0), Rough);
+
+        assert_eq!(solve(SIZE, &cells, &[], (0, 3)), Some(vec![Right, Up, Left, Down]));
+
+        cells.insert((3, 3), Cracked);
+
+        assert_eq!(solve(SIZE, &cells, &[], (0, 3)), None);
+    }
+
+    #[test]
+    fn solve_fetches_every_key_before_counting_the_goal() {
+        // The door is open once the key is picked up, and the goal is active
+        // once the other key is too. Stopping above the door needs rough
+        // ground there, at `(3, 0)`.
+        //
+        // S . . . . . . a
+        // . . . . . . . .
+        // # # # A # # # #
+        // . . . . . . . .
+        // . . . G . . . .
+        // . . . . . . . .
+        // . . . . . . . .
+        // . . . . . . . b
+        let mut cells = cells_from(&[(0, 2), (1, 2), (2, 2), (4, 2), (5, 2), (6, 2), (7, 2)],
+                                   (3, 4));
+        cells.insert((3, 2), Door(0));
+        cells.insert((7, 0), Key(0));
+
+        let path = slide(SIZE, &cells, &[], (0, 0), Right);
+        assert_eq!((path.stop, path.collected), (Some((7, 0)), vec![(7, 0)]));
+        assert_eq!(solve(SIZE, &cells, &[], (0, 0)), None);
+
+        cells.insert((3, 0), Rough);
+
+        assert_eq!(solve(SIZE, &cells, &[], (0, 0)), Some(vec![Right, Right, Left, Down]));
+
+        cells.insert((7, 7), Key(1));
+
+        assert_eq!(solve(SIZE, &cells, &[], (0, 0)), None);
+    }
+
+    #[test]
+    fn arrows_in_a_loop_mean_a_slide_never_stops() {
+        // → ↓
+        // ↑ ←
+        let mut cells = cells_from(&[], (7, 7));
+        cells.insert((2, 2), Arrow(Right));
+        cells.insert((3, 2), Arrow(Down));
+        cells.insert((3, 3), Arrow(Left));
+        cells.insert((2, 3), Arrow(Up));
+
+        assert_eq!(slide(SIZE, &cells, &[], (0, 2), Right).stop, None);
+        //and the search does not get stuck going round it either
+        assert_eq!(solve(SIZE, &cells, &[], (0, 2)).map(|moves| moves.len()), Some(2));
+    }
+
+    #[test]
+    fn solve_keeps_searching_from_stops_first_passed_over_mid_slide() {
+        // Sliding right goes down the arrow, passing over (2, 1). The only way
+        // to stop there is from below, against the arrow, and the goal can
+        // only be reached from there.
+        //
+        // S . v
+        // . G .
+        // . . .
+        let size = Size::new(3, 3);
+        let mut cells = cells_from(&[], (1, 1));
+        cells.insert((2, 0), Arrow(Down));
+
+        assert_eq!(solve(size, &cells, &[], (0, 0)), Some(vec![Right, Up, Left]));
+        assert_eq!(move_counts(size, &cells, &[], (0, 0)).get(&(1, 1)), Some(&3));
+    }
+
+    #[test]
+    fn solve_returns_no_moves_when_starting_on_the_goal() {
+        let cells = cells_from(&[], (3, 3));
+
+        assert_eq!(solve(SIZE, &cells, &[], (3, 3)), Some(vec![]));
+    }
+
+    #[test]
+    fn solve_counts_passing_over_the_goal_mid_slide() {
+        let cells = cells_from(&[], (3, 0));
+
+        assert_eq!(solve(SIZE, &cells, &[], (0, 0)), Some(vec![Right]));
+    }
+
+    #[test]
+    fn solve_finds_the_shortest_solution_on_this_example() {
+        // The only way onto the goal is down from above it, where the wall on
+        // the top row lets us stop.
+        //
+        // . . . . . # . .
+        // . . . . G . . .
+        // . . . . # . . .
+        // S . . . . . . .
+        let cells = cells_from(&[(4, 2), (5, 0)], (4, 1));
+
+        assert_eq!(solve(SIZE, &cells, &[], (0, 3)), Some(vec![Up, Right, Down]));
+    }
+
+    #[test]
+    fn solve_returns_none_when_the_goal_is_walled_off() {
+        let cells = cells_from(&[(3, 4), (5, 4), (4, 3), (4, 5)], (4, 4));
+
+        assert_eq!(solve(SIZE, &cells, &[], (0, 0)), None);
+    }
+
+    #[test]
+    fn solve_handles_boards_larger_than_the_default() {
+        let size = Size::new(64, 64);
+        let cells = cells_from(&[(63, 1)], (62, 63));
+
+        assert_eq!(solve(size, &cells, &[], (0, 0)), Some(vec![Down, Right]));
+    }
+}
@@ -1,17 +1,20 @@
 use platform_types::{
+    unscaled,
     State,
     StateParams,
+    Storage,
 };
 
 use softbuffer::GraphicsContext;
 
 use winit::{
+    dpi::PhysicalPosition,
     event::{Event, WindowEvent},
     event_loop::{EventLoop, ControlFlow},
-    window::WindowBuilder,
+    window::{Window, WindowBuilder},
 };
 
-use render::{clip, FrameBuffer, NeedsRedraw};
+use render::{clip, FrameBuffer, Letterbox, NeedsRedraw};
 
 pub fn run<S: State + 'static>(mut state: S) {
     let event_loop = EventLoop::new();
@@ -44,8 +47,18 @@ pub fn run<S: State + 'static>(mut state: S) {
     let mut loop_helper = spin_sleep::LoopHelper::builder()
             .build_with_target_rate(60.0);
 
+    let timestep = 1.0 / f64::from(platform_types::UPDATES_PER_SECOND);
+    // Time that has passed, but that the game has not been updated for yet.
+    let mut accumulator = 0.0;
+    let mut last_time = now_seconds();
+
     let mut just_gained_focus = true;
 
+    // The finger that is acting as the pointer, if any.
+    let mut touch_id = None;
+    // Scrolling that has not added up to a whole step yet.
+    let mut scroll_remainder = 0.0;
+
     event_loop.run(move |event, _, control_flow| {
         let window = graphics_context.window();
 
@@ -92,6 +105,76 @@ pub fn run<S: State + 'static>(mut state: S) {
                     ElementState::Released => state.release(button),
                 }
             }
+            Event::WindowEvent {
+                event: WindowEvent::CursorMoved { position, .. },
+                window_id,
+            } if window_id == window.id() => {
+                state.pointer_moved(to_command_xy(window, position));
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CursorLeft { .. },
+                window_id,
+            } if window_id == window.id() => {
+                state.pointer_moved(None);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::MouseInput {
+                    state: element_state,
+                    button: winit::event::MouseButton::Left,
+                    ..
+                },
+                window_id,
+            } if window_id == window.id() => {
+                use winit::event::ElementState;
+
+                match element_state {
+                    ElementState::Pressed => state.pointer_pressed(),
+                    ElementState::Released => state.pointer_released(),
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::MouseWheel { delta, .. },
+                window_id,
+            } if window_id == window.id() => {
+                use winit::event::MouseScrollDelta;
+
+                // winit counts scrolling "up", away from the user, as positive.
+                scroll_remainder -= match delta {
+                    MouseScrollDelta::LineDelta(_, y) => f64::from(y),
+                    MouseScrollDelta::PixelDelta(position) => position.y / PIXELS_PER_SCROLL_STEP,
+                };
+
+                let steps = scroll_remainder.trunc();
+                scroll_remainder -= steps;
+
+                if steps != 0.0 {
+                    state.scrolled(steps as i32);
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::Touch(winit::event::Touch { phase, location, id, .. }),
+                window_id,
+            } if window_id == window.id() => {
+                use winit::event::TouchPhase;
+
+                // Only the first finger down acts as the pointer, until it
+                // is lifted.
+                match phase {
+                    TouchPhase::Started if touch_id.is_none() => {
+                        touch_id = Some(id);
+                        state.pointer_moved(to_command_xy(window, location));
+                        state.pointer_pressed();
+                    }
+                    TouchPhase::Moved if touch_id == Some(id) => {
+                        state.pointer_moved(to_command_xy(window, location));
+                    }
+                    TouchPhase::Ended | TouchPhase::Cancelled if touch_id == Some(id) => {
+                        touch_id = None;
+                        state.pointer_released();
+                    }
+                    _ => (),
+                }
+            }
             Event::WindowEvent {
                 event: WindowEvent::Focused(true),
                 window_id,
@@ -99,6 +182,18 @@ pub fn run<S: State + 'static>(mut state: S) {
                 just_gained_focus = true;
             }
             Event::MainEventsCleared => {
+                let time = now_seconds();
+                accumulator = f64::min(
+                    accumulator + (time - last_time),
+                    MAX_CATCH_UP_SECONDS
+                );
+                last_time = time;
+
+                while accumulator >= timestep {
+                    state.update(timestep as f32);
+                    accumulator -= timestep;
+                }
+
                 let (commands, sounds) = state.frame();
 
                 handle_sounds(&mut sound_handler, sounds);
@@ -136,10 +231,49 @@ pub fn run<S: State + 'static>(mut state: S) {
     });
 }
 
+/// Where `position`, in the window, is on the screen the commands draw to.
+/// This undoes the scaling and letterboxing that `render::render` does.
+fn to_command_xy(window: &Window, position: PhysicalPosition<f64>) -> Option<unscaled::XY> {
+    #[cfg(target_arch = "wasm32")]
+    let position = wasm::to_canvas_position(window, position);
+
+    let size = window.inner_size();
+
+    Letterbox::from_size((size.width as clip::W, size.height as clip::H))
+        .to_command_xy(position.x, position.y)
+}
+
+/// Trackpads and the like report scrolling in pixels rather than steps of
+/// the wheel. This is roughly how many make a step.
+const PIXELS_PER_SCROLL_STEP: f64 = 40.0;
+
+/// After a long gap between frames, for example while the window is being
+/// dragged, at most this much time is caught up on, rather than the game
+/// running for many updates in a row.
+const MAX_CATCH_UP_SECONDS: f64 = 0.25;
+
+/// Seconds since some fixed point in the past.
+#[cfg(not(target_arch = "wasm32"))]
+fn now_seconds() -> f64 {
+    use std::{sync::OnceLock, time::Instant};
+
+    static START: OnceLock<Instant> = OnceLock::new();
+
+    START.get_or_init(Instant::now).elapsed().as_secs_f64()
+}
+
+/// Seconds since some fixed point in the past.
+#[cfg(target_arch = "wasm32")]
+fn now_seconds() -> f64 {
+    // `std::time::Instant` is not available on the web.
+    js_sys::Date::now() / 1000.0
+}
+
 #[cfg(target_arch = "wasm32")]
 mod wasm {
     use winit::{
-        window::WindowBuilder,
+        dpi::{LogicalPosition, PhysicalPosition},
+        window::{Window, WindowBuilder},
         platform::web::WindowBuilderExtWebSys,
     };
     use wasm_bindgen::JsCast;
@@ -171,6 +305,29 @@ mod wasm {
         style.remove_property("height").unwrap();
     }
 
+    /// The browser stretches the canvas to fit the page, so a position on
+    /// it, which winit reports in scaled CSS pixels, needs scaling to the
+    /// canvas's own pixels.
+    pub fn to_canvas_position(
+        window: &Window,
+        position: PhysicalPosition<f64>,
+    ) -> PhysicalPosition<f64> {
+        let canvas = get_canvas();
+        let client_width = f64::from(canvas.client_width());
+        let client_height = f64::from(canvas.client_height());
+
+        if client_width <= 0.0 || client_height <= 0.0 {
+            return position
+        }
+
+        let css: LogicalPosition<f64> = position.to_logical(window.scale_factor());
+
+        PhysicalPosition::new(
+            css.x * f64::from(canvas.width()) / client_width,
+            css.y * f64::from(canvas.height()) / client_height,
+        )
+    }
+
     fn get_canvas() -> HtmlCanvasElement {
         let window = web_sys::window().unwrap();
         let document = window.document().unwrap();
@@ -231,19 +388,55 @@ pub fn get_state_params() -> StateParams {
         console::error_1(&s.into());
     }
 
-    let time = Date::new_0().get_time();
+    let now = Date::new_0();
+    let time = now.get_time();
 
     let seed = unsafe {
         core::mem::transmute::<[f64; 2], [u8; 16]>([time, 1.0 / time])
     };
 
+    let today = platform_types::Date {
+        year: now.get_utc_full_year() as u16,
+        // JS months start at 0.
+        month: now.get_utc_month() as u8 + 1,
+        day: now.get_utc_date() as u8,
+    };
+
     (
         seed,
         Some(logger),
         Some(error_logger),
+        today,
+        Box::new(LocalStorage),
     )
 }
 
+#[cfg(target_arch = "wasm32")]
+struct LocalStorage;
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorage {
+    const KEY: &'static str = "ice-puzzles-save";
+
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Storage for LocalStorage {
+    fn load(&self) -> Option<String> {
+        Self::storage()?.get_item(Self::KEY).ok()?
+    }
+
+    fn save(&mut self, data: &str) -> Result<(), String> {
+        Self::storage()
+            .ok_or_else(|| "localStorage is not available".to_string())?
+            .set_item(Self::KEY, data)
+            .map_err(|e| format!("{:?}", e))
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub fn get_state_params() -> StateParams {
     fn logger(s: &str) {
@@ -258,9 +451,55 @@ pub fn get_state_params() -> StateParams {
         new_seed(),
         Some(logger),
         Some(error_logger),
+        today(),
+        Box::new(FileStorage { path: save_path() }),
     )
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+struct FileStorage {
+    path: Option<std::path::PathBuf>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for FileStorage {
+    fn load(&self) -> Option<String> {
+        std::fs::read_to_string(self.path.as_ref()?).ok()
+    }
+
+    fn save(&mut self, data: &str) -> Result<(), String> {
+        let path = self.path.as_ref()
+            .ok_or_else(|| "Could not find a data directory to save to".to_string())?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("{}: {}", parent.display(), e))?;
+        }
+
+        std::fs::write(path, data)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// Where the usual data directory for each OS is, with our own directory in it.
+#[cfg(not(target_arch = "wasm32"))]
+fn save_path() -> Option<std::path::PathBuf> {
+    use std::{env::var_os, path::PathBuf};
+
+    let data_dir = if cfg!(target_os = "windows") {
+        var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    };
+
+    data_dir.map(|dir| dir.join("ice-puzzles").join("save.txt"))
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn new_seed() -> xs::Seed {
     let time = std::time::SystemTime::now()
@@ -273,6 +512,19 @@ fn new_seed() -> xs::Seed {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn today() -> platform_types::Date {
+    const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+    let time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+
+    platform_types::Date::from_days_since_unix_epoch(
+        (time.as_secs() / SECONDS_PER_DAY) as i64
+    )
+}
+
 #[cfg(target_arch = "wasm32")]
 use wasm::{init_sound_handler, handle_sounds};
 
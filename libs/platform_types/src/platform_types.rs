@@ -396,7 +396,7 @@ pub mod unscaled {
         }
     }
 
-    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
     pub struct WH {
         pub w: W,
         pub h: H,
@@ -543,7 +543,7 @@ pub type PaletteIndex = u8;
 
 
 pub mod sprite {
-    pub use super::unscaled::{W, H};
+    pub use super::unscaled::{W, H, WH};
 
     pub type Inner = u16;
     #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -649,9 +649,11 @@ pub mod command {
     //pub const WIDTH: Inner = 480;
     //pub const HEIGHT: Inner = 320;
 
-    // Need more pixels to fit the current art
+    // Need more pixels to fit a 16x16 board of the current art at full size,
+    // and a strip under the board for the HUD. Other board sizes are scaled to
+    // fit the same space.
     pub const WIDTH: Inner = 45 * 16;
-    pub const HEIGHT: Inner = 45 * 16;
+    pub const HEIGHT: Inner = 45 * 16 + 16;
 
     pub const LENGTH: usize = WIDTH as usize * HEIGHT as usize;
 
@@ -986,6 +988,9 @@ pub mod command {
     pub struct Command {
         pub rect: Rect,
         pub sprite_xy: sprite::XY,
+        /// The sprite is stretched or squashed to fill `rect`, if this is a
+        /// different size.
+        pub sprite_wh: sprite::WH,
         pub colour_override: ARGB,
     }    
 }
@@ -995,6 +1000,20 @@ pub use command::Command;
 pub struct Input {
     pub gamepad: Button,
     pub previous_gamepad: Button,
+    /// Where the pointer, (the mouse, or a touch,) is on the screen the
+    /// commands draw to. `None` if it is over the letterbox bars, or has left
+    /// the window.
+    pub pointer: Option<unscaled::XY>,
+    /// Whether the primary mouse button is held down, or a finger is touching.
+    pub pointer_down: bool,
+    pub previous_pointer_down: bool,
+    /// How many times the pointer went down since the last update. A quick
+    /// tap can go down and back up again between updates, so `pointer_down`
+    /// alone would miss it.
+    pub clicks: u8,
+    /// How many steps the mouse wheel turned since the last update. Positive
+    /// when scrolled "down", towards the user.
+    pub scroll: i32,
 }
 
 impl Input {
@@ -1007,9 +1026,15 @@ impl Input {
     pub fn released_this_frame(&self, buttons: Button) -> bool {
         self.previous_gamepad.contains(buttons) && !self.gamepad.contains(buttons)
     }
+
+    /// Whether the pointer came back up since the last update, including
+    /// after a tap that also went down since then.
+    pub fn pointer_released_this_frame(&self) -> bool {
+        !self.pointer_down && (self.previous_pointer_down || self.clicks > 0)
+    }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SFX {
     CardPlace,
     CardSlide,
@@ -1085,14 +1110,97 @@ pub use button::Button;
 
 pub type Logger = Option<fn(&str) -> ()>;
 
-pub type StateParams = ([u8; 16], Logger, Logger);
+pub mod date {
+    /// A calendar day, in UTC.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct Date {
+        pub year: u16,
+        /// 1 to 12.
+        pub month: u8,
+        /// 1 to 31.
+        pub day: u8,
+    }
+
+    impl Date {
+        pub fn from_days_since_unix_epoch(days: i64) -> Self {
+            // Adapted from the `civil_from_days` algorithm described at
+            // http://howardhinnant.github.io/date_algorithms.html
+            let z = days + 719_468;
+            let era = z.div_euclid(146_097);
+            let day_of_era = z.rem_euclid(146_097);
+            let year_of_era = (
+                day_of_era
+                - day_of_era / 1460
+                + day_of_era / 36_524
+                - day_of_era / 146_096
+            ) / 365;
+            let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+            // March is month 0 here, so the leap day lands at the end of the year.
+            let shifted_month = (5 * day_of_year + 2) / 153;
+
+            let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+            let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+            let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+            Self {
+                year: year as u16,
+                month: month as u8,
+                day: day as u8,
+            }
+        }
+    }
+
+    #[test]
+    fn from_days_since_unix_epoch_works_on_these_examples() {
+        assert_eq!(Date::from_days_since_unix_epoch(0), Date { year: 1970, month: 1, day: 1 });
+        assert_eq!(Date::from_days_since_unix_epoch(11_016), Date { year: 2000, month: 2, day: 29 });
+        assert_eq!(Date::from_days_since_unix_epoch(11_017), Date { year: 2000, month: 3, day: 1 });
+        assert_eq!(Date::from_days_since_unix_epoch(20_743), Date { year: 2026, month: 10, day: 17 });
+    }
+}
+pub use date::Date;
+
+/// Somewhere to keep data between runs of the program.
+pub trait Storage {
+    /// Returns `None` if nothing has been saved yet, or it could not be read.
+    fn load(&self) -> Option<String>;
+
+    fn save(&mut self, data: &str) -> Result<(), String>;
+}
+
+pub type StateParams = ([u8; 16], Logger, Logger, Date, Box<dyn Storage>);
+
+/// How many times a second `State::update` is called, however often frames
+/// are shown. Keeping this fixed means the game runs at the same speed
+/// everywhere.
+pub const UPDATES_PER_SECOND: u32 = 60;
 
 pub trait State {
+    /// Advances the game by `dt` seconds.
+    fn update(&mut self, dt: f32);
+
+    /// What to draw, and what to play, for what has happened since the last
+    /// frame. There may have been any number of updates since then, including
+    /// none.
     fn frame(&mut self) -> (&[Command], &[SFX]);
 
     fn press(&mut self, button: Button);
 
     fn release(&mut self, button: Button);
+
+    /// The pointer moved to `xy` on the screen the commands draw to, or off
+    /// of it, if `None`.
+    fn pointer_moved(&mut self, xy: Option<unscaled::XY>);
+
+    /// The primary mouse button went down, or a finger touched the screen,
+    /// wherever the pointer last moved to.
+    fn pointer_pressed(&mut self);
+
+    fn pointer_released(&mut self);
+
+    /// The mouse wheel turned by `delta` steps. Positive when scrolled "down",
+    /// towards the user.
+    fn scrolled(&mut self, delta: i32);
 }
 
 // reportedly colourblind friendly colours
@@ -75,6 +75,7 @@ mod hash {
                 y_max,
             },
             sprite_xy,
+            sprite_wh,
             colour_override,
         } = command;
 
@@ -85,6 +86,8 @@ mod hash {
 
         u16(hash, sprite_xy.x.0);
         u16(hash, sprite_xy.y.0);
+        u16(hash, sprite_wh.w.0);
+        u16(hash, sprite_wh.h.0);
 
         bytes(hash, &colour_override.to_ne_bytes());
     }
@@ -989,6 +992,115 @@ mod wide_tests {
     }
 }
 
+/// How the screen the commands draw to fits into a frame buffer: scaled up by
+/// the largest whole number that fits, then centred between bars.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Letterbox {
+    pub multiplier: u16,
+    pub left_bar_width: clip::W,
+    pub top_bar_height: clip::H,
+}
+
+impl Letterbox {
+    /// A frame buffer smaller than the commands' screen is treated as being
+    /// exactly that size, as `render` does.
+    pub fn from_size((width, height): (clip::W, clip::H)) -> Self {
+        let (width, height) = if width < command::WIDTH
+        || height < command::HEIGHT {
+            (command::WIDTH, command::HEIGHT)
+        } else {
+            (width, height)
+        };
+
+        let width_multiplier = width / command::WIDTH;
+        let height_multiplier = height / command::HEIGHT;
+        let multiplier = core::cmp::min(width_multiplier, height_multiplier);
+
+        let vertical_bars_width: clip::W = width - (multiplier * command::WIDTH);
+
+        let horizontal_bars_height: clip::H = height - (multiplier * command::HEIGHT);
+
+        Self {
+            multiplier,
+            // Any odd pixel goes on the left, and the top.
+            left_bar_width: (vertical_bars_width + 1) / 2,
+            top_bar_height: (horizontal_bars_height + 1) / 2,
+        }
+    }
+
+    /// Where the frame buffer position `(x, y)` is on the commands' screen,
+    /// or `None` if it is on one of the bars, or outside the frame buffer.
+    /// Windowing systems can report positions between pixels, hence the
+    /// floats.
+    pub fn to_command_xy(self, x: f64, y: f64) -> Option<unscaled::XY> {
+        let multiplier = f64::from(self.multiplier);
+        let x = (x - f64::from(self.left_bar_width)) / multiplier;
+        let y = (y - f64::from(self.top_bar_height)) / multiplier;
+
+        // Written so NaNs land outside, too.
+        if !(0.0..f64::from(command::WIDTH)).contains(&x)
+        || !(0.0..f64::from(command::HEIGHT)).contains(&y) {
+            return None
+        }
+
+        Some(unscaled::XY {
+            x: unscaled::X(x as unscaled::Inner),
+            y: unscaled::Y(y as unscaled::Inner),
+        })
+    }
+}
+
+#[cfg(test)]
+mod letterbox_to_command_xy {
+    use super::*;
+
+    fn xy(x: unscaled::Inner, y: unscaled::Inner) -> Option<unscaled::XY> {
+        Some(unscaled::XY { x: unscaled::X(x), y: unscaled::Y(y) })
+    }
+
+    #[test]
+    fn maps_the_corners_when_the_frame_buffer_is_exactly_the_commands_size() {
+        let letterbox = Letterbox::from_size((command::WIDTH, command::HEIGHT));
+
+        assert_eq!(letterbox.to_command_xy(0.0, 0.0), xy(0, 0));
+        assert_eq!(
+            letterbox.to_command_xy(
+                f64::from(command::WIDTH) - 0.5,
+                f64::from(command::HEIGHT) - 0.5,
+            ),
+            xy(command::WIDTH - 1, command::HEIGHT - 1)
+        );
+        assert_eq!(letterbox.to_command_xy(f64::from(command::WIDTH), 0.0), None);
+    }
+
+    #[test]
+    fn undoes_the_multiplier_and_skips_the_bars() {
+        // Room for twice the size, with 10 extra pixels across.
+        let letterbox = Letterbox::from_size((
+            command::WIDTH * 2 + 10,
+            command::HEIGHT * 2,
+        ));
+
+        assert_eq!(letterbox.multiplier, 2);
+        assert_eq!(letterbox.left_bar_width, 5);
+
+        assert_eq!(letterbox.to_command_xy(4.0, 0.0), None);
+        assert_eq!(letterbox.to_command_xy(5.0, 0.0), xy(0, 0));
+        assert_eq!(letterbox.to_command_xy(8.0, 3.0), xy(1, 1));
+        assert_eq!(
+            letterbox.to_command_xy(f64::from(command::WIDTH * 2 + 5), 0.0),
+            None
+        );
+    }
+
+    #[test]
+    fn returns_none_for_nan() {
+        let letterbox = Letterbox::from_size((command::WIDTH, command::HEIGHT));
+
+        assert_eq!(letterbox.to_command_xy(f64::NAN, 0.0), None);
+    }
+}
+
 pub fn render(
     frame_buffer: &mut FrameBuffer,
     commands: &[Command],
@@ -999,32 +1111,22 @@ pub fn render(
         frame_buffer.height = command::HEIGHT;
     }
 
-    let width_multiplier = frame_buffer.width / command::WIDTH;
-    let height_multiplier = frame_buffer.height / command::HEIGHT;
-    let multiplier = core::cmp::min(width_multiplier, height_multiplier);
+    let Letterbox {
+        multiplier,
+        left_bar_width,
+        top_bar_height,
+    } = Letterbox::from_size((frame_buffer.width, frame_buffer.height));
     if multiplier == 0 {
         debug_assert!(multiplier != 0);
         return NeedsRedraw::No;
     }
 
-    let vertical_bars_width: clip::W = frame_buffer.width - (multiplier * command::WIDTH);
-
-    let left_bar_width: clip::W = (vertical_bars_width + 1) / 2;
-
-    let right_bar_width: clip::W = vertical_bars_width / 2;
-
-    let horizontal_bars_height: clip::H = frame_buffer.height - (multiplier * command::HEIGHT);
-
-    let top_bar_height: clip::H = (horizontal_bars_height + 1) / 2;
-
-    let bottom_bar_height: clip::H = horizontal_bars_height / 2;
-
     let outer_clip_rect = clip::Rect {
         x: left_bar_width..(
-            frame_buffer.width - right_bar_width
+            left_bar_width + multiplier * command::WIDTH
         ),
         y: top_bar_height..(
-            frame_buffer.height - bottom_bar_height
+            top_bar_height + multiplier * command::HEIGHT
         ),
     };
 
@@ -1074,6 +1176,7 @@ pub fn render(
 
     for &Command {
         sprite_xy: sprite::XY { x: sprite_x, y: sprite_y },
+        sprite_wh: sprite::WH { w: sprite_w, h: sprite_h },
         colour_override,
         rect,
     } in commands.iter() {
@@ -1103,6 +1206,14 @@ pub fn render(
 
         let sprite_x = usize::from(sprite_x);
         let sprite_y = usize::from(sprite_y);
+        let sprite_w = usize::from(sprite_w.get());
+        let sprite_h = usize::from(sprite_h.get());
+
+        let unscaled::Rect { w: rect_w, h: rect_h, .. } = rect.unscaled();
+        let rect_w = usize::from(rect_w.get());
+        let rect_h = usize::from(rect_h.get());
+
+        let is_scaled = sprite_w != rect_w || sprite_h != rect_h;
 
         let src_w = GFX_WIDTH as usize;
 
@@ -1128,21 +1239,42 @@ pub fn render(
                     )
                 };
 
-                let base_src_i =
-                    (sprite_y + y_iter_count) * src_w
-                    + (sprite_x + x_iter_count);
-                debug_assert!(
-                    base_src_i < GFX.len(), 
-                    "({sprite_y} + {y_iter_count}) * {src_w} + ({sprite_x} + {x_iter_count})
+                let gfx_colours = if is_scaled {
+                    // Nearest neighbour, so each lane needs its own source pixel.
+                    let src_row_start =
+                        (sprite_y + y_iter_count * sprite_h / rect_h) * src_w
+                        + sprite_x;
+
+                    let src_i = |lane: usize| {
+                        let src_x = (x_iter_count + lane) * sprite_w / rect_w;
+                        // Lanes past the end of the rect are not written, but
+                        // should still not read past the end of the sprite.
+                        src_row_start + core::cmp::min(src_x, sprite_w.saturating_sub(1))
+                    };
+
+                    wide::i32x4!(
+                        GFX[src_i(0)] as i32,
+                        GFX[src_i(1)] as i32,
+                        GFX[src_i(2)] as i32,
+                        GFX[src_i(3)] as i32,
+                    )
+                } else {
+                    let base_src_i =
+                        (sprite_y + y_iter_count) * src_w
+                        + (sprite_x + x_iter_count);
+                    debug_assert!(
+                        base_src_i < GFX.len(), 
+                        "({sprite_y} + {y_iter_count}) * {src_w} + ({sprite_x} + {x_iter_count})
 {base_src_i} >= {}
 ({x_min} to {x_end}, {y_min} to {y_end})",
-                    GFX.len()
-                );
-                let gfx_colours = unsafe {
-                    wide::load!(
-                        GFX.as_ptr(),
-                        base_src_i
-                    )
+                        GFX.len()
+                    );
+                    unsafe {
+                        wide::load!(
+                            GFX.as_ptr(),
+                            base_src_i
+                        )
+                    }
                 };
 
                 let is_full_alpha_mask = wide::eq_mask_u32!(